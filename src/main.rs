@@ -4,13 +4,13 @@ use std::io::Read;
 
 mod solutions;
 
-#[macro_use]
 extern crate lazy_static;
 
 #[derive(Parser, Debug)]
 enum CLIArgs {
     Run(RunCommand),
     Download(DownloadCommand),
+    Inspect(InspectCommand),
 }
 
 #[derive(Args, Debug)]
@@ -25,6 +25,13 @@ struct DownloadCommand {
     day: Option<usize>,
 }
 
+#[derive(Args, Debug)]
+struct InspectCommand {
+    day: usize,
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv::dotenv().ok();
@@ -39,6 +46,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Ok(())
         }
         CLIArgs::Download(c) => download(get_day(c.day)).await,
+        CLIArgs::Inspect(c) => {
+            solutions::inspect(c.day, c.args);
+
+            Ok(())
+        }
     }
 }
 
@@ -95,7 +107,7 @@ fn append_day(selected_day: usize) -> Result<(), std::io::Error> {
     contents = contents.replace("mod solver;", &format!("{new_mod}\nmod solver;"));
 
     let new_match = format!(
-        "{selected_day} => day{:02}::Problem.solve(filename, parts),",
+        "{selected_day} => day{:02}::Problem.run(filename, mode),",
         selected_day
     );
     contents = contents.replace("_ => panic!", &format!("{new_match}\n        _ => panic!"));
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A simulation that came back to a state it had already been in, so it
+/// repeats from there on.
+pub struct Cycle {
+    /// Steps before the loop starts.
+    pub start: usize,
+    /// Steps in each turn of the loop.
+    pub length: usize,
+    /// The metric after each step up to the repeat, [0] being before any.
+    metrics: Vec<u64>,
+}
+
+impl Cycle {
+    /// How much the metric grows with each turn of the loop.
+    pub fn gain(&self) -> u64 {
+        self.metrics[self.start + self.length] - self.metrics[self.start]
+    }
+
+    /// The metric after `steps` steps, however many that is.
+    pub fn extrapolate(&self, steps: u64) -> u64 {
+        if let Some(metric) = self.metrics.get(steps as usize) {
+            return *metric;
+        }
+
+        let after_start = steps - self.start as u64;
+        let turns = after_start / self.length as u64;
+        let rest = (after_start % self.length as u64) as usize;
+        self.metrics[self.start + rest] + turns * self.gain()
+    }
+}
+
+/// Runs `step` on `state` until its fingerprint repeats, recording `metric`
+/// along the way. The fingerprint must hold everything the following steps
+/// depend on, and the metric must only ever grow.
+pub fn find_cycle<S, F: Eq + Hash>(
+    state: &mut S,
+    mut step: impl FnMut(&mut S),
+    mut fingerprint: impl FnMut(&S) -> F,
+    mut metric: impl FnMut(&S) -> u64,
+) -> Cycle {
+    let mut seen = HashMap::from([(fingerprint(state), 0)]);
+    let mut metrics = vec![metric(state)];
+
+    loop {
+        step(state);
+        metrics.push(metric(state));
+        let steps = metrics.len() - 1;
+        if let Some(start) = seen.insert(fingerprint(state), steps) {
+            return Cycle {
+                start,
+                length: steps - start,
+                metrics,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extrapolates_past_the_repeat() {
+        // Walks 0, 1, 2, 3, 4, 2, 3, 4, ... adding the position each step
+        let mut state = (0, 0);
+        let step = |(pos, total): &mut (u64, u64)| {
+            *pos = if *pos == 4 { 2 } else { *pos + 1 };
+            *total += *pos;
+        };
+        let cycle = find_cycle(&mut state, step, |s| s.0, |s| s.1);
+        assert_eq!((cycle.start, cycle.length), (2, 3));
+        assert_eq!(cycle.gain(), 9);
+
+        let mut brute = (0, 0);
+        let mut totals = vec![0];
+        for _ in 0..50 {
+            step(&mut brute);
+            totals.push(brute.1);
+        }
+        for (steps, total) in totals.iter().enumerate() {
+            assert_eq!(
+                cycle.extrapolate(steps as u64),
+                *total,
+                "after {steps} steps"
+            );
+        }
+    }
+}
@@ -1,73 +1,148 @@
+use itertools::Itertools;
+
+use super::solver::{keep_file, number, rewind};
 use super::Solver;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 pub struct Problem;
 
-struct Acc {
-    current: usize,
-    max: usize,
+/// Keeps the `n` highest calorie totals seen so far, plus every elf tied
+/// with the lowest of them. Elves are numbered from 1, like in the puzzle.
+struct TopElves {
+    n: usize,
+    top: BTreeMap<usize, Vec<usize>>,
+    kept: usize,
+    count: usize,
+    /// Highest total and every elf holding it, whatever `n` is.
+    max: Option<(usize, Vec<usize>)>,
 }
-struct Acc2 {
-    current: usize,
-    values: Vec<usize>,
+
+impl TopElves {
+    fn new(n: usize) -> Self {
+        TopElves {
+            n,
+            top: BTreeMap::new(),
+            kept: 0,
+            count: 0,
+            max: None,
+        }
+    }
+
+    fn add(&mut self, calories: usize) {
+        self.count += 1;
+        match &mut self.max {
+            Some((max, holders)) if calories == *max => holders.push(self.count),
+            Some((max, _)) if calories < *max => {}
+            _ => self.max = Some((calories, vec![self.count])),
+        }
+
+        if self.n == 0 {
+            return;
+        }
+        if let Some(lowest) = self.top.keys().next() {
+            if self.kept >= self.n && calories < *lowest {
+                return;
+            }
+        }
+
+        self.top.entry(calories).or_default().push(self.count);
+        self.kept += 1;
+
+        while let Some((&lowest, elves)) = self.top.iter().next() {
+            if self.kept - elves.len() < self.n {
+                break;
+            }
+            self.kept -= elves.len();
+            self.top.remove(&lowest);
+        }
+    }
+
+    /// (elf, calories) from the highest total down, ties included.
+    fn top(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.top
+            .iter()
+            .rev()
+            .flat_map(|(calories, elves)| elves.iter().map(|elf| (*elf, *calories)))
+    }
+
+    /// Sum of exactly `n` totals, without counting ties twice.
+    fn top_sum(&self) -> usize {
+        self.top().take(self.n).map(|(_, calories)| calories).sum()
+    }
+
+    fn max(&self) -> Option<(usize, &[usize])> {
+        self.max
+            .as_ref()
+            .map(|(calories, elves)| (*calories, elves.as_slice()))
+    }
+}
+
+/// Streams an inventory list, so the input never needs to fit in memory.
+fn read_elves<R: BufRead>(mut reader: R, n: usize) -> Result<TopElves, String> {
+    let mut elves = TopElves::new(n);
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut current: Option<usize> = None;
+
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(|e| format!("{e}"))?;
+        line_number += 1;
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            if let Some(calories) = current.take() {
+                elves.add(calories);
+            }
+            if read == 0 {
+                return Ok(elves);
+            }
+        } else {
+            let calories: usize = trimmed
+                .parse()
+                .map_err(|e| format!("line {line_number}: {e}"))?;
+            current = Some(current.unwrap_or(0) + calories);
+        }
+    }
 }
 
 impl Solver for Problem {
-    type Input = Vec<String>;
+    type Input = File;
     type Output1 = usize;
     type Output2 = usize;
 
     fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
-        file_reader.lines().map(|x| x.unwrap()).collect()
+        keep_file(file_reader)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
-        let res = input.iter().fold(Acc { current: 0, max: 0 }, |acc, x| {
-            if x == "" {
-                Acc {
-                    current: 0,
-                    max: acc.max.max(acc.current),
-                }
-            } else {
-                Acc {
-                    current: acc.current + x.parse::<usize>().unwrap(),
-                    max: acc.max,
-                }
-            }
-        });
-
-        Ok(res.max.max(res.current))
+        Ok(read_elves(rewind(input), 1)?.top_sum())
     }
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
-        let mut res = input.iter().fold(
-            Acc2 {
-                current: 0,
-                values: Vec::new(),
-            },
-            |mut acc, x| {
-                if x == "" {
-                    acc.values.push(acc.current);
-                    Acc2 {
-                        current: 0,
-                        values: acc.values,
-                    }
-                } else {
-                    Acc2 {
-                        current: acc.current + x.parse::<usize>().unwrap(),
-                        values: acc.values,
-                    }
-                }
-            },
-        );
-
-        res.values.push(res.current);
-        res.values.sort();
-        res.values.reverse();
-
-        let res2 = &res.values[..3];
-
-        Ok(res2.iter().sum())
+        Ok(read_elves(rewind(input), 3)?.top_sum())
+    }
+
+    /// Lists the top `[n]` elves (3 by default) and who holds the maximum.
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        let n = args.first().map_or(Ok(3), |v| number(v))?;
+        let elves = read_elves(rewind(input), n)?;
+
+        println!("{} elves, top {n} (ties included):", elves.count);
+        for (elf, calories) in elves.top() {
+            println!("Elf {elf}: {calories} calories");
+        }
+        println!("Top {n} total: {}", elves.top_sum());
+        if let Some((calories, holders)) = elves.max() {
+            let who = if holders.len() == 1 { "elf" } else { "elves" };
+            println!(
+                "Maximum of {calories} calories held by {who} {}",
+                holders.iter().join(", ")
+            );
+        }
+
+        Ok(())
     }
 }
@@ -1,15 +1,12 @@
+use super::intervals::IntervalSet;
 use super::Solver;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 pub struct Problem;
 
-fn merge((lf, ls): (usize, usize), (rf, rs): (usize, usize)) -> (usize, usize) {
-    (lf.min(rf), ls.max(rs))
-}
-
 impl Solver for Problem {
-    type Input = Vec<((usize, usize), (usize, usize))>;
+    type Input = Vec<(IntervalSet, IntervalSet)>;
     type Output1 = usize;
     type Output2 = usize;
 
@@ -20,12 +17,12 @@ impl Solver for Problem {
             .map(|line| {
                 let mut x = line.split(",");
                 let mut l = x.next().unwrap().split("-");
-                let lf: usize = l.next().unwrap().parse().unwrap();
-                let ls: usize = l.next().unwrap().parse().unwrap();
+                let lf: isize = l.next().unwrap().parse().unwrap();
+                let ls: isize = l.next().unwrap().parse().unwrap();
                 let mut r = x.next().unwrap().split("-");
-                let rf: usize = r.next().unwrap().parse().unwrap();
-                let rs: usize = r.next().unwrap().parse().unwrap();
-                ((lf, ls), (rf, rs))
+                let rf: isize = r.next().unwrap().parse().unwrap();
+                let rs: isize = r.next().unwrap().parse().unwrap();
+                (IntervalSet::range(lf, ls), IntervalSet::range(rf, rs))
             })
             .collect()
     }
@@ -33,10 +30,7 @@ impl Solver for Problem {
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
         let res = input
             .iter()
-            .filter(|(left, right)| {
-                let merged = merge(*left, *right);
-                return *left == merged || *right == merged;
-            })
+            .filter(|(left, right)| left.covers(right) || right.covers(left))
             .count();
 
         Ok(res)
@@ -45,12 +39,7 @@ impl Solver for Problem {
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
         let res = input
             .iter()
-            .filter(|((lf, ls), (rf, rs))| {
-                return rf <= lf && lf <= rs
-                    || rf <= ls && ls <= rs
-                    || lf <= rf && rf <= ls
-                    || lf <= rs && rs <= ls;
-            })
+            .filter(|(left, right)| !left.intersection(right).is_empty())
             .count();
 
         Ok(res)
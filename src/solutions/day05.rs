@@ -1,7 +1,9 @@
 use itertools::Itertools;
 use regex::Regex;
 
+use super::solver::number;
 use super::Solver;
+use std::fmt::Display;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::str::FromStr;
@@ -14,18 +16,18 @@ impl Solver for Problem {
     type Output2 = String;
 
     fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
-        let mut lines = file_reader.lines().map(|x| x.unwrap());
-
-        let stack_lines = lines.by_ref().take_while(|x| x.contains("[")).collect_vec();
-        let reversed_lines = stack_lines.into_iter().rev().collect_vec();
-        let mut crates = Crates::new(&reversed_lines[0]);
-        reversed_lines
-            .into_iter()
-            .skip(1)
-            .for_each(|line| crates.push_line(&line));
-
-        let operations: Vec<Operation> = lines
-            .skip_while(|x| !x.contains("move"))
+        let lines = file_reader.lines().map(|x| x.unwrap()).collect_vec();
+        let (diagram, rest) = lines.split_at(
+            lines
+                .iter()
+                .position(|x| x.trim().is_empty())
+                .unwrap_or(lines.len()),
+        );
+
+        let crates: Crates = diagram.join("\n").parse().unwrap();
+        let operations: Vec<Operation> = rest
+            .iter()
+            .filter(|x| !x.trim().is_empty())
             .map(|x| x.parse().unwrap())
             .collect_vec();
 
@@ -33,67 +35,313 @@ impl Solver for Problem {
     }
 
     fn solve_first(&self, (crates, operations): &Self::Input) -> Result<Self::Output1, String> {
-        let mut crates_clone = crates.clone();
+        let mut simulator = Simulator::new(crates, operations, &CrateMover9000);
+        while simulator.step()?.is_some() {}
 
-        operations.iter().for_each(|operation| {
-            crates_clone.apply(operation);
-        });
-
-        Ok(crates_clone.get_top())
+        Ok(simulator.crates().get_top())
     }
 
     fn solve_second(&self, (crates, operations): &Self::Input) -> Result<Self::Output2, String> {
-        let mut crates_clone = crates.clone();
+        let mut simulator = Simulator::new(crates, operations, &CrateMover9001);
+        while simulator.step()?.is_some() {}
 
-        operations.iter().for_each(|operation| {
-            crates_clone.apply_batch(operation);
-        });
+        Ok(simulator.crates().get_top())
+    }
+
+    /// `simulate [crane]` replays the operations, drawing the stacks after
+    /// each one. `undo [crane]` applies them all and then reverts them one by
+    /// one. The crane is `9000` (default), `9001` or `capped <n>`.
+    /// `diagram` writes the parsed stacks back in the input format.
+    fn inspect(&self, (crates, operations): &Self::Input, args: &[String]) -> Result<(), String> {
+        let (command, crane_args) = args
+            .split_first()
+            .ok_or_else(|| "usage: simulate|undo [crane] | diagram".to_owned())?;
+
+        if command == "diagram" {
+            println!("{crates}");
+            return Ok(());
+        }
 
-        Ok(crates_clone.get_top())
+        let crane = parse_crane(crane_args)?;
+        let mut simulator = Simulator::new(crates, operations, crane.as_ref());
+        match command.as_str() {
+            "simulate" => {
+                println!("{}", simulator.crates());
+                while let Some(operation) = simulator.step()? {
+                    println!("{operation}");
+                    println!("{}", simulator.crates());
+                }
+                println!("Top: {}", simulator.crates().get_top());
+            }
+            "undo" => {
+                while simulator.step()?.is_some() {}
+                println!("{}", simulator.crates());
+                while let Some(operation) = simulator.undo() {
+                    println!("undo {operation}");
+                    println!("{}", simulator.crates());
+                }
+                println!("Back to the start: {}", simulator.crates() == crates);
+            }
+            _ => return Err(format!("unknown command {command}")),
+        }
+
+        Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
+fn parse_crane(args: &[String]) -> Result<Box<dyn Crane>, String> {
+    match args.iter().map(|a| a.as_str()).collect_vec().as_slice() {
+        [] | ["9000"] => Ok(Box::new(CrateMover9000)),
+        ["9001"] => Ok(Box::new(CrateMover9001)),
+        ["capped", capacity] => Ok(Box::new(CappedCrane(number(capacity)?))),
+        _ => Err("crane should be 9000, 9001 or capped <n>".to_owned()),
+    }
+}
+
+/// How many crates a crane picks up at once.
+pub trait Crane {
+    /// Size of the next lift when `remaining` crates are left to move. The
+    /// lifted crates keep their order.
+    fn next_lift(&self, remaining: usize) -> usize;
+}
+
+/// Moves one crate at a time.
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn next_lift(&self, _: usize) -> usize {
+        1
+    }
+}
+
+/// Moves all the crates in a single lift.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn next_lift(&self, remaining: usize) -> usize {
+        remaining
+    }
+}
+
+/// Moves crates in batches of up to its capacity.
+pub struct CappedCrane(usize);
+
+impl Crane for CappedCrane {
+    fn next_lift(&self, remaining: usize) -> usize {
+        remaining.min(self.0)
+    }
+}
+
+/// Applies operations one at a time so the stacks can be looked at in
+/// between, keeping what's needed to undo each of them.
+pub struct Simulator<'a> {
+    crates: Crates,
+    operations: &'a [Operation],
+    crane: &'a dyn Crane,
+    history: Vec<Undo>,
+}
+
+impl<'a> Simulator<'a> {
+    pub fn new(crates: &Crates, operations: &'a [Operation], crane: &'a dyn Crane) -> Self {
+        Simulator {
+            crates: crates.clone(),
+            operations,
+            crane,
+            history: vec![],
+        }
+    }
+
+    /// Applies the next operation and returns it, or `None` once all are
+    /// done. Operations that don't fit the stacks leave them untouched.
+    pub fn step(&mut self) -> Result<Option<&'a Operation>, String> {
+        let index = self.history.len();
+        let operation = match self.operations.get(index) {
+            None => return Ok(None),
+            Some(operation) => operation,
+        };
+
+        let undo = self
+            .crates
+            .apply(self.crane, operation)
+            .map_err(|e| format!("operation {} ({operation}): {e}", index + 1))?;
+        self.history.push(undo);
+
+        Ok(Some(operation))
+    }
+
+    /// Reverts the last applied operation and returns it.
+    pub fn undo(&mut self) -> Option<&'a Operation> {
+        let undo = self.history.pop()?;
+        self.crates.undo(undo);
+        Some(&self.operations[self.history.len()])
+    }
+
+    pub fn crates(&self) -> &Crates {
+        &self.crates
+    }
+}
+
+/// Crates an operation took, in their original order, so it can be reverted.
+pub struct Undo {
+    from: usize,
+    to: usize,
+    lifted: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Crates {
     stacks: Vec<Vec<String>>,
 }
 
-impl Crates {
-    pub fn new(base: &str) -> Self {
-        let crates = base.split(" ").map(|x| x[1..2].to_owned()).collect_vec();
-        let stacks = crates.into_iter().map(|x| Vec::from([x])).collect_vec();
-        Crates { stacks: stacks }
-    }
-    pub fn push_line(&mut self, line: &str) {
-        for i in 0..self.stacks.len() {
-            let crate_name = &line[(1 + i * 4)..(1 + i * 4 + 1)];
-            if crate_name != " " {
-                self.stacks[i].push(crate_name.to_owned())
+/// Character offset of the middle of a match, doubled to stay an integer.
+fn double_center(line: &str, start: usize, end: usize) -> usize {
+    let offset = line[..start].chars().count();
+    offset * 2 + line[start..end].chars().count()
+}
+
+/// Reads a stack diagram. Columns are found from the stack labels in the
+/// last line, and each crate goes to the label closest to it, so names can
+/// be any length and the spacing doesn't have to be exact.
+impl FromStr for Crates {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        lazy_static::lazy_static! {
+            static ref CRATE_RGX: Regex = Regex::new(r"\[([^\]]+)\]").unwrap();
+            static ref LABEL_RGX: Regex = Regex::new(r"\S+").unwrap();
+        }
+
+        let lines = s.lines().filter(|l| !l.trim().is_empty()).collect_vec();
+        let (label_line, crate_lines) = lines
+            .split_last()
+            .ok_or_else(|| "empty stack diagram".to_owned())?;
+
+        let centers = LABEL_RGX
+            .find_iter(label_line)
+            .enumerate()
+            .map(|(i, m)| match m.as_str().parse::<usize>() {
+                Ok(label) if label == i + 1 => Ok(double_center(label_line, m.start(), m.end())),
+                _ => Err(format!("stack label '{}', expected {}", m.as_str(), i + 1)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stacks: Vec<Vec<String>> = vec![vec![]; centers.len()];
+        for (row, line) in crate_lines.iter().rev().enumerate() {
+            if !CRATE_RGX.replace_all(line, "").trim().is_empty() {
+                return Err(format!("unexpected text in '{line}'"));
+            }
+
+            for captures in CRATE_RGX.captures_iter(line) {
+                let m = captures.get(0).unwrap();
+                let center = double_center(line, m.start(), m.end());
+                let stack = (0..centers.len())
+                    .min_by_key(|i| centers[*i].abs_diff(center))
+                    .ok_or_else(|| "crates without stack labels".to_owned())?;
+
+                if stacks[stack].len() != row {
+                    return Err(format!(
+                        "crate [{}] is not right on top of stack {}",
+                        &captures[1],
+                        stack + 1
+                    ));
+                }
+                stacks[stack].push(captures[1].to_owned());
             }
         }
+
+        Ok(Crates { stacks })
     }
-    pub fn apply(&mut self, operation: &Operation) {
-        // println!("{:?} {:?}", self, operation);
-        for _ in 0..operation.quantity {
-            let top_crate = self.stacks[operation.from].pop().unwrap();
-            self.stacks[operation.to].push(top_crate);
+}
+
+impl Crates {
+    pub fn validate(&self, operation: &Operation) -> Result<(), String> {
+        for stack in [operation.from, operation.to] {
+            if stack >= self.stacks.len() {
+                return Err(format!(
+                    "there's no stack {}, only {}",
+                    stack + 1,
+                    self.stacks.len()
+                ));
+            }
         }
+
+        let available = self.stacks[operation.from].len();
+        if operation.quantity > available {
+            return Err(format!(
+                "stack {} only has {available} crates",
+                operation.from + 1
+            ));
+        }
+
+        Ok(())
     }
-    pub fn apply_batch(&mut self, operation: &Operation) {
+
+    pub fn apply(&mut self, crane: &dyn Crane, operation: &Operation) -> Result<Undo, String> {
+        self.validate(operation)?;
+
         let from_crate = &self.stacks[operation.from];
-        let base = from_crate.len() - operation.quantity;
+        let lifted = from_crate[from_crate.len() - operation.quantity..].to_vec();
 
-        for i in base..from_crate.len() {
-            let str = self.stacks[operation.from][i].to_owned();
-            self.stacks[operation.to].push(str);
+        let mut remaining = operation.quantity;
+        while remaining > 0 {
+            let lift = crane.next_lift(remaining).clamp(1, remaining);
+            let from_crate = &mut self.stacks[operation.from];
+            let moved = from_crate.split_off(from_crate.len() - lift);
+            self.stacks[operation.to].extend(moved);
+            remaining -= lift;
         }
-        self.stacks[operation.from].truncate(base);
+
+        Ok(Undo {
+            from: operation.from,
+            to: operation.to,
+            lifted,
+        })
     }
+
+    pub fn undo(&mut self, undo: Undo) {
+        let to_crate = &mut self.stacks[undo.to];
+        to_crate.truncate(to_crate.len() - undo.lifted.len());
+        self.stacks[undo.from].extend(undo.lifted);
+    }
+
     pub fn get_top(&self) -> String {
-        self.stacks
+        self.stacks.iter().filter_map(|stack| stack.last()).join("")
+    }
+}
+
+/// Draws the stacks the same way the input does. Columns widen to fit the
+/// longest crate name or label.
+impl Display for Crates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .stacks
             .iter()
-            .map(|stack| stack[stack.len() - 1].to_owned())
-            .join("")
+            .flatten()
+            .map(|name| name.chars().count())
+            .chain((1..=self.stacks.len()).map(|i| i.to_string().len()))
+            .max()
+            .unwrap_or(1)
+            + 2;
+
+        let height = self.stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+        for row in (0..height).rev() {
+            let line = self
+                .stacks
+                .iter()
+                .map(|stack| match stack.get(row) {
+                    Some(name) => format!("{:<width$}", format!("[{name}]")),
+                    None => " ".repeat(width),
+                })
+                .join(" ");
+            writeln!(f, "{line}")?;
+        }
+        write!(
+            f,
+            "{}",
+            (1..=self.stacks.len())
+                .map(|i| format!("{:^width$}", i))
+                .join(" ")
+        )
     }
 }
 
@@ -104,6 +352,18 @@ pub struct Operation {
     to: usize,
 }
 
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.quantity,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
+
 impl FromStr for Operation {
     type Err = String;
 
@@ -117,6 +377,9 @@ impl FromStr for Operation {
             Some(captures) => {
                 let from: usize = captures[2].parse().unwrap();
                 let to: usize = captures[3].parse().unwrap();
+                if from == 0 || to == 0 {
+                    return Err(format!("stacks are numbered from 1 in '{s}'"));
+                }
 
                 Ok(Operation {
                     quantity: captures[1].parse().unwrap(),
@@ -127,3 +390,67 @@ impl FromStr for Operation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crates(stacks: &[&[&str]]) -> Crates {
+        Crates {
+            stacks: stacks
+                .iter()
+                .map(|s| s.iter().map(|name| name.to_string()).collect())
+                .collect(),
+        }
+    }
+
+    /// Parses `diagram`, checks it's `expected` and that writing it out
+    /// parses back to the same stacks.
+    fn round_trip(diagram: &str, expected: &Crates) -> String {
+        let parsed: Crates = diagram.parse().unwrap();
+        assert_eq!(&parsed, expected);
+        let written = parsed.to_string();
+        assert_eq!(&written.parse::<Crates>().unwrap(), expected);
+        written
+    }
+
+    #[test]
+    fn puzzle_example_is_written_back_exactly() {
+        let diagram = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+        let expected = crates(&[&["Z", "N"], &["M", "C", "D"], &["P"]]);
+        assert_eq!(round_trip(diagram, &expected), diagram);
+    }
+
+    #[test]
+    fn long_crate_names_widen_the_columns() {
+        let diagram = "[AB]\n[C]  [DEF]\n 1    2";
+        let expected = crates(&[&["C", "AB"], &["DEF"]]);
+        assert_eq!(
+            round_trip(diagram, &expected),
+            "[AB]       \n[C]   [DEF]\n  1     2  "
+        );
+    }
+
+    #[test]
+    fn more_than_nine_stacks() {
+        let names = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L"];
+        let stacks = names.iter().map(std::slice::from_ref).collect_vec();
+        let expected = crates(&stacks);
+        round_trip(
+            "[A] [B] [C] [D] [E] [F] [G] [H] [I] [J] [K] [L]\n 1   2   3   4   5   6   7   8   9  10  11  12",
+            &expected,
+        );
+        let written = round_trip(&expected.to_string(), &expected);
+        assert!(written.ends_with("  10   11   12 "));
+    }
+
+    #[test]
+    fn trailing_whitespace_does_not_matter() {
+        let expected = crates(&[&["Z", "N"], &["M", "C", "D"], &["P"]]);
+        round_trip("    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3", &expected);
+        round_trip(
+            "    [D]        \n[N] [C]  \n[Z] [M] [P]   \n 1   2   3    \n",
+            &expected,
+        );
+    }
+}
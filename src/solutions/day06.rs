@@ -1,59 +1,123 @@
-use itertools::Itertools;
-
+use super::solver::{keep_file, number, rewind};
 use super::Solver;
-use std::collections::HashSet;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Bytes};
 
 pub struct Problem;
 
-fn has_duplicates(chr: &[char]) -> bool {
-    let chr_set: HashSet<char> = HashSet::from_iter(chr.iter().cloned());
-    // println!("{:?} {:?}, {}", chr, chr_set, chr_set.len() != chr.len());
+/// Sliding window over the last `length` characters that knows in O(1)
+/// whether they're all different.
+struct Window {
+    buffer: Vec<u8>,
+    counts: [usize; 256],
+    duplicates: usize,
+    seen: usize,
+}
+
+impl Window {
+    fn new(length: usize) -> Self {
+        Window {
+            buffer: vec![0; length],
+            counts: [0; 256],
+            duplicates: 0,
+            seen: 0,
+        }
+    }
+
+    /// Adds a character and returns whether the window now holds a marker.
+    fn push(&mut self, c: u8) -> bool {
+        let slot = self.seen % self.buffer.len();
+        if self.seen >= self.buffer.len() {
+            let old = self.buffer[slot] as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 1 {
+                self.duplicates -= 1;
+            }
+        }
+
+        self.buffer[slot] = c;
+        self.counts[c as usize] += 1;
+        if self.counts[c as usize] == 2 {
+            self.duplicates += 1;
+        }
+        self.seen += 1;
+
+        self.seen >= self.buffer.len() && self.duplicates == 0
+    }
+}
+
+/// Positions (counted in characters, like the puzzle) right after every
+/// marker in a stream, read one byte at a time.
+struct Markers<R: BufRead> {
+    bytes: Bytes<R>,
+    window: Window,
+}
+
+impl<R: BufRead> Markers<R> {
+    fn new(reader: R, length: usize) -> Result<Self, String> {
+        if length == 0 {
+            return Err("markers need at least one character".to_owned());
+        }
+        Ok(Markers {
+            bytes: reader.bytes(),
+            window: Window::new(length),
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for Markers<R> {
+    type Item = Result<usize, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for byte in self.bytes.by_ref() {
+            match byte {
+                Err(e) => return Some(Err(format!("{e}"))),
+                Ok(c) if c.is_ascii_whitespace() => {}
+                Ok(c) => {
+                    if self.window.push(c) {
+                        return Some(Ok(self.window.seen));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
 
-    chr_set.len() != chr.len()
+fn first_marker(input: &File, length: usize) -> Result<usize, String> {
+    Markers::new(rewind(input), length)?
+        .next()
+        .unwrap_or_else(|| Err("Not found".to_owned()))
 }
 
 impl Solver for Problem {
-    type Input = Vec<char>;
+    type Input = File;
     type Output1 = usize;
     type Output2 = usize;
 
     fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
-        file_reader
-            .lines()
-            .map(|x| x.unwrap())
-            .flat_map(|line| line.chars().collect_vec())
-            .collect()
+        keep_file(file_reader)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
-        let input_clone = input.clone();
-
-        let res = input
-            .iter()
-            .enumerate()
-            .skip(4)
-            .find_position(|(i, _)| !has_duplicates(&input_clone[(i - 4)..*i]));
-
-        match res {
-            None => Err("Not found".to_owned()),
-            Some((pos, _)) => Ok(pos + 4),
-        }
+        first_marker(input, 4)
     }
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
-        let input_clone = input.clone();
+        first_marker(input, 14)
+    }
 
-        let res = input
-            .iter()
-            .enumerate()
-            .skip(14)
-            .find_position(|(i, _)| !has_duplicates(&input_clone[(i - 14)..*i]));
+    /// Prints every marker position for a window of `[length]` (4 by default).
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        let length = args.first().map_or(Ok(4), |v| number(v))?;
 
-        match res {
-            None => Err("Not found".to_owned()),
-            Some((pos, _)) => Ok(pos + 14),
+        let mut count = 0;
+        for position in Markers::new(rewind(input), length)? {
+            println!("{}", position?);
+            count += 1;
         }
+        println!("{count} markers of length {length}");
+
+        Ok(())
     }
 }
@@ -1,15 +1,19 @@
 use itertools::Itertools;
+use serde_json::{json, Value};
 use slab_tree::{NodeId, Tree, TreeBuilder};
 
+use super::solver::split_flags;
 use super::Solver;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::Display;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::num::ParseIntError;
 use std::str::FromStr;
 
 pub struct Problem;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum NodeType {
     Dir,
     File(usize),
@@ -22,89 +26,425 @@ pub struct Node {
 }
 
 impl FromStr for Node {
-    type Err = ParseIntError;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.starts_with("dir") {
-            Ok(Node {
-                node_type: NodeType::Dir,
-                name: s[4..].to_owned(),
-            })
-        } else {
-            let r = s.split(" ").collect_vec();
-            Ok(Node {
-                node_type: NodeType::File(r[0].parse()?),
-                name: r[1].to_owned(),
-            })
+        let (first, name) = s
+            .split_once(' ')
+            .ok_or_else(|| format!("wrong listing entry '{s}'"))?;
+        if name.is_empty() || name.contains('/') {
+            return Err(format!("wrong entry name '{name}'"));
         }
+
+        let node_type = match first {
+            "dir" => NodeType::Dir,
+            size => NodeType::File(size.parse().map_err(|e| format!("{e} in '{s}'"))?),
+        };
+
+        Ok(Node {
+            node_type,
+            name: name.to_owned(),
+        })
     }
 }
 
-impl Solver for Problem {
-    type Input = Tree<Node>;
-    type Output1 = usize;
-    type Output2 = usize;
+/// Directory tree rebuilt from a terminal session. Directories are created
+/// as soon as they're seen, either listed or `cd`'d into, and listing the same
+/// directory more than once only checks the entries against what's known.
+pub struct FileSystem {
+    tree: Tree<Node>,
+    /// Recursive size of every node, filled in once the transcript is read.
+    sizes: HashMap<NodeId, usize>,
+}
 
-    fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
-        let mut tree = TreeBuilder::new()
-            .with_root(Node {
-                node_type: NodeType::Dir,
-                name: "/".to_owned(),
-            })
-            .build();
-        let root_id = tree.root_id().unwrap();
-        let mut cd_id = root_id;
+impl FileSystem {
+    fn from_transcript(lines: &[String]) -> Result<Self, String> {
+        let mut fs = FileSystem {
+            tree: TreeBuilder::new()
+                .with_root(Node {
+                    node_type: NodeType::Dir,
+                    name: "/".to_owned(),
+                })
+                .build(),
+            sizes: HashMap::new(),
+        };
+        let mut cd_id = fs.root_id();
+        // Entries seen by the `ls` in progress, if any
+        let mut listing: Option<Vec<NodeId>> = None;
 
-        let lines = file_reader.lines().map(|x| x.unwrap()).collect_vec();
+        for (i, line) in lines.iter().enumerate() {
+            let error = |e: String| format!("line {}: {e}", i + 1);
 
-        for line in lines {
-            let split = line.split(" ").collect_vec();
-            if split[0] == "$" {
-                if split[1] == "cd" {
-                    match split[2] {
-                        ".." => {
-                            cd_id = tree.get(cd_id).unwrap().parent().unwrap().node_id();
-                        }
-                        "/" => {
-                            cd_id = root_id;
-                        }
-                        dir => {
-                            let cd = tree.get(cd_id).unwrap();
-                            let child_option = cd.children().find(|c| c.data().name == dir);
-                            let child = child_option.expect("cd dir: child not found");
-                            cd_id = child.node_id();
-                        }
+            match line.strip_prefix("$ ") {
+                Some(command) => {
+                    if let Some(seen) = listing.take() {
+                        fs.check_listing(cd_id, &seen).map_err(error)?;
+                    }
+                    let split = command.split_whitespace().collect_vec();
+                    match split.as_slice() {
+                        ["cd", path] => cd_id = fs.cd(cd_id, path).map_err(error)?,
+                        ["ls"] => listing = Some(vec![]),
+                        _ => return Err(error(format!("unknown command '{command}'"))),
+                    }
+                }
+                None => match listing.as_mut() {
+                    None => return Err(error(format!("output '{line}' outside of ls"))),
+                    Some(seen) => {
+                        let node: Node = line.parse().map_err(error)?;
+                        seen.push(fs.add(cd_id, node).map_err(error)?);
                     }
+                },
+            }
+        }
+        if let Some(seen) = listing {
+            fs.check_listing(cd_id, &seen)
+                .map_err(|e| format!("line {}: {e}", lines.len()))?;
+        }
+
+        fs.sizes = fs.total_sizes();
+        Ok(fs)
+    }
+
+    /// Adds up every node's size bottom-up, children before their parent.
+    fn total_sizes(&self) -> HashMap<NodeId, usize> {
+        let mut sizes = HashMap::new();
+        for node in self.tree.root().unwrap().traverse_post_order() {
+            let size = match node.data().node_type {
+                NodeType::File(size) => size,
+                NodeType::Dir => node.children().map(|c| sizes[&c.node_id()]).sum(),
+            };
+            sizes.insert(node.node_id(), size);
+        }
+        sizes
+    }
+
+    fn root_id(&self) -> NodeId {
+        self.tree.root_id().unwrap()
+    }
+
+    fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        self.tree
+            .get(dir)
+            .unwrap()
+            .children()
+            .find(|c| c.data().name == name)
+            .map(|c| c.node_id())
+    }
+
+    fn add(&mut self, dir: NodeId, node: Node) -> Result<NodeId, String> {
+        match self.child(dir, &node.name) {
+            Some(id) => {
+                let existing = self.tree.get(id).unwrap().data();
+                if existing.node_type != node.node_type {
+                    return Err(format!(
+                        "{} was {:?}, now listed as {:?}",
+                        self.path(id),
+                        existing.node_type,
+                        node.node_type
+                    ));
                 }
+                Ok(id)
+            }
+            None => Ok(self.tree.get_mut(dir).unwrap().append(node).node_id()),
+        }
+    }
+
+    /// Every entry already known in `dir` must show up when it's listed.
+    fn check_listing(&self, dir: NodeId, seen: &[NodeId]) -> Result<(), String> {
+        let node = self.tree.get(dir).unwrap();
+        match node.children().find(|c| !seen.contains(&c.node_id())) {
+            None => Ok(()),
+            Some(missing) => Err(format!(
+                "{} is missing from the listing of {}",
+                missing.data().name,
+                self.path(dir)
+            )),
+        }
+    }
+
+    /// Follows a relative or absolute path, creating directories that
+    /// weren't listed yet.
+    fn cd(&mut self, from: NodeId, path: &str) -> Result<NodeId, String> {
+        let mut cd_id = if path.starts_with('/') {
+            self.root_id()
+        } else {
+            from
+        };
+
+        for part in path.split('/').filter(|p| !p.is_empty() && *p != ".") {
+            cd_id = match part {
+                ".." => self
+                    .tree
+                    .get(cd_id)
+                    .unwrap()
+                    .parent()
+                    .ok_or_else(|| "cd .. from /".to_owned())?
+                    .node_id(),
+                dir => self.add(
+                    cd_id,
+                    Node {
+                        node_type: NodeType::Dir,
+                        name: dir.to_owned(),
+                    },
+                )?,
+            };
+        }
+
+        Ok(cd_id)
+    }
+
+    /// Looks up a directory or file by absolute path.
+    pub fn find(&self, path: &str) -> Option<NodeId> {
+        let mut id = self.root_id();
+        for part in path.split('/').filter(|p| !p.is_empty() && *p != ".") {
+            id = match part {
+                ".." => self.tree.get(id).unwrap().parent()?.node_id(),
+                name => self.child(id, name)?,
+            };
+        }
+        Some(id)
+    }
+
+    pub fn path(&self, id: NodeId) -> String {
+        let node = self.tree.get(id).unwrap();
+        if node.parent().is_none() {
+            return "/".to_owned();
+        }
+        let mut names = node
+            .ancestors()
+            .filter(|a| a.parent().is_some())
+            .map(|a| a.data().name.as_str())
+            .collect_vec();
+        names.reverse();
+        names.push(node.data().name.as_str());
+
+        format!("/{}", names.join("/"))
+    }
+
+    /// Recursive size of whatever is at `id`.
+    pub fn size(&self, id: NodeId) -> usize {
+        self.sizes[&id]
+    }
+
+    pub fn size_of(&self, path: &str) -> Result<usize, String> {
+        self.find(path)
+            .map(|id| self.size(id))
+            .ok_or_else(|| format!("{path} not found"))
+    }
+
+    /// Children of `id`, biggest first when `sort` is set.
+    fn entries(&self, id: NodeId, sort: bool) -> Vec<(NodeId, usize)> {
+        let entries = self
+            .tree
+            .get(id)
+            .unwrap()
+            .children()
+            .map(|c| (c.node_id(), self.size(c.node_id())));
+        if sort {
+            entries.sorted_by_key(|(_, size)| Reverse(*size)).collect()
+        } else {
+            entries.collect()
+        }
+    }
+
+    /// Draws the tree below `id` like `tree` does, skipping anything smaller
+    /// than `options.min`.
+    pub fn render_tree(&self, id: NodeId, options: &ReportOptions) -> String {
+        let mut out = format!(
+            "{} ({})\n",
+            self.tree.get(id).unwrap().data(),
+            options.format_size(self.size(id))
+        );
+        self.render_children(id, options, "", &mut out);
+        out
+    }
+
+    fn render_children(&self, id: NodeId, options: &ReportOptions, prefix: &str, out: &mut String) {
+        let entries = self
+            .entries(id, options.sort)
+            .into_iter()
+            .filter(|(_, size)| *size >= options.min.unwrap_or(0))
+            .collect_vec();
+
+        for (i, (child, size)) in entries.iter().enumerate() {
+            let last = i == entries.len() - 1;
+            let (branch, indent) = if last {
+                ("└── ", "    ")
             } else {
-                let node: Node = line.parse().unwrap();
-                let mut cd = tree.get_mut(cd_id).unwrap();
-                cd.append(node);
+                ("├── ", "│   ")
+            };
+            out.push_str(&format!(
+                "{prefix}{branch}{} ({})\n",
+                self.tree.get(*child).unwrap().data(),
+                options.format_size(*size)
+            ));
+            self.render_children(*child, options, &format!("{prefix}{indent}"), out);
+        }
+    }
+
+    /// Every directory below `id` with its recursive size, children before
+    /// their parent like `du`, within `options.min` and `options.max`.
+    pub fn du(&self, id: NodeId, options: &ReportOptions) -> Vec<(String, usize)> {
+        let mut dirs = vec![];
+        self.collect_dirs(id, options, &mut dirs);
+        if options.sort {
+            dirs.sort_by_key(|(_, size)| Reverse(*size));
+        }
+        dirs
+    }
+
+    fn collect_dirs(&self, id: NodeId, options: &ReportOptions, dirs: &mut Vec<(String, usize)>) {
+        if self.tree.get(id).unwrap().data().node_type != NodeType::Dir {
+            return;
+        }
+        for (child, _) in self.entries(id, false) {
+            self.collect_dirs(child, options, dirs);
+        }
+
+        let size = self.size(id);
+        if options.min.is_none_or(|min| size >= min) && options.max.is_none_or(|max| size <= max) {
+            dirs.push((self.path(id), size));
+        }
+    }
+
+    pub fn to_json(&self, id: NodeId) -> Value {
+        let node = self.tree.get(id).unwrap().data();
+        match node.node_type {
+            NodeType::File(size) => json!({
+                "name": node.name,
+                "type": "file",
+                "size": size,
+            }),
+            NodeType::Dir => json!({
+                "name": node.name,
+                "type": "dir",
+                "size": self.size(id),
+                "children": self
+                    .entries(id, false)
+                    .into_iter()
+                    .map(|(child, _)| self.to_json(child))
+                    .collect_vec(),
+            }),
+        }
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.node_type {
+            NodeType::Dir if self.name == "/" => write!(f, "/"),
+            NodeType::Dir => write!(f, "{}/", self.name),
+            NodeType::File(_) => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Flags shared by the `tree` and `du` reports.
+#[derive(Default)]
+pub struct ReportOptions {
+    human: bool,
+    sort: bool,
+    min: Option<usize>,
+    max: Option<usize>,
+}
+
+impl ReportOptions {
+    /// Splits `args` into options and the remaining positional arguments.
+    fn parse(args: &[String]) -> Result<(Self, Vec<&String>), String> {
+        let mut options = ReportOptions::default();
+        let positional = split_flags(args, |flag| {
+            match flag.name() {
+                "-h" | "--human" => options.human = true,
+                "--sort" => options.sort = true,
+                "--min" => options.min = Some(flag.number()?),
+                "--max" => options.max = Some(flag.number()?),
+                _ => return Ok(false),
             }
+            Ok(true)
+        })?;
+
+        Ok((options, positional))
+    }
+
+    fn format_size(&self, size: usize) -> String {
+        if !self.human {
+            return format!("{size}");
         }
 
-        tree
+        let units = ["", "K", "M", "G", "T"];
+        let mut value = size as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < units.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        match unit {
+            0 => format!("{size}"),
+            _ if value < 10.0 => format!("{value:.1}{}", units[unit]),
+            _ => format!("{value:.0}{}", units[unit]),
+        }
     }
+}
 
-    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
-        // let mut s = String::new();
-        // input.write_formatted(&mut s).unwrap();
-        // println!("{}", s);
+impl Solver for Problem {
+    type Input = FileSystem;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
+        let lines = file_reader.lines().map(|x| x.unwrap()).collect_vec();
+
+        FileSystem::from_transcript(&lines).unwrap()
+    }
 
-        let (_, sum) = sum_dir_sizes(&input, input.root_id().unwrap(), 100000);
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
+        let (_, sum) = sum_dir_sizes(&input.tree, input.root_id(), 100000);
 
         Ok(sum)
     }
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
-        let (total, _) = sum_dir_sizes(&input, input.root_id().unwrap(), 0);
+        let total = input.size(input.root_id());
         let unused_space = 70000000 - total;
         let needs_to_delete = 30000000 - unused_space;
 
-        let (_, res) = find_smallest_freeing(&input, input.root_id().unwrap(), needs_to_delete);
+        let (_, res) = find_smallest_freeing(&input.tree, input.root_id(), needs_to_delete);
 
         Ok(res.unwrap())
     }
+
+    /// `size <path>...` prints the recursive size of each path.
+    /// `tree [path]` and `du [path]` draw reports, taking `--human`, `--sort`,
+    /// `--min <size>` and (for `du`) `--max <size>`.
+    /// `json [path]` exports the tree.
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        let (command, rest) = args
+            .split_first()
+            .ok_or_else(|| "usage: size|tree|du|json".to_owned())?;
+        let (options, positional) = ReportOptions::parse(rest)?;
+        let find = |path: Option<&&String>| {
+            let path = path.map_or("/", |p| p.as_str());
+            input.find(path).ok_or_else(|| format!("{path} not found"))
+        };
+
+        match command.as_str() {
+            "size" => {
+                for path in positional {
+                    println!("{}\t{path}", input.size_of(path)?);
+                }
+            }
+            "tree" => print!("{}", input.render_tree(find(positional.first())?, &options)),
+            "du" => {
+                for (path, size) in input.du(find(positional.first())?, &options) {
+                    println!("{}\t{path}", options.format_size(size));
+                }
+            }
+            "json" => println!("{}", input.to_json(find(positional.first())?)),
+            _ => return Err(format!("unknown command {command}")),
+        }
+
+        Ok(())
+    }
 }
 
 fn sum_dir_sizes(tree: &Tree<Node>, cd_id: NodeId, max_size: usize) -> (usize, usize) {
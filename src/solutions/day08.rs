@@ -1,149 +1,192 @@
 use itertools::Itertools;
-use ndarray::Array2;
 
+use super::grid::{Grid, Pos, ORTHOGONAL};
+use super::solver::number;
 use super::Solver;
-use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 pub struct Problem;
 
-impl Solver for Problem {
-    type Input = Array2<u8>;
-    type Output1 = usize;
-    type Output2 = usize;
+/// Trees that can be seen looking from `from` in `direction`, up to and
+/// including the first one that's at least as tall.
+fn viewing(
+    trees: &Grid<u8>,
+    from: Pos,
+    direction: (isize, isize),
+) -> impl Iterator<Item = Pos> + '_ {
+    let height = trees[from];
+    let mut blocked = false;
+    trees.ray(from, direction).take_while(move |pos| {
+        let seen = !blocked;
+        blocked = blocked || trees[*pos] >= height;
+        seen
+    })
+}
 
-    fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
-        let lines = file_reader
-            .lines()
-            .map(|x| x.unwrap())
-            .map(|line| line.bytes().map(|c| c - b'0').collect_vec())
-            .collect_vec();
+/// Whether the tree at `pos` can be seen from outside the grid.
+fn is_visible(trees: &Grid<u8>, pos: Pos) -> bool {
+    ORTHOGONAL.iter().any(|direction| {
+        trees
+            .ray(pos, *direction)
+            .all(|other| trees[other] < trees[pos])
+    })
+}
 
-        ndarray::Array2::from_shape_fn((lines.len(), lines[0].len()), |(r, c)| lines[r][c])
-    }
+fn scenic_score(trees: &Grid<u8>, pos: Pos) -> usize {
+    ORTHOGONAL
+        .iter()
+        .map(|direction| viewing(trees, pos, *direction).count())
+        .product()
+}
 
-    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
-        let shape = input.shape();
-        let mut visible_set: HashSet<(usize, usize)> = HashSet::new();
-
-        for x in 1..(shape[0] - 1) {
-            let mut max = input[[x, 0]];
-            for y in 1..(shape[1] - 1) {
-                if max == 9 {
-                    break;
-                }
-                let v = input[[x, y]];
-                if v > max {
-                    visible_set.insert((x, y));
-                    max = v;
-                }
-            }
+/// Visibility from outside the grid and scenic score of every tree.
+pub struct Analysis {
+    visible: Grid<bool>,
+    scenic: Grid<usize>,
+}
 
-            max = input[[x, shape[1] - 1]];
-            // print!("{max}");
-            for y in (1..(shape[1] - 1)).rev() {
-                if max == 9 {
-                    break;
-                }
-                let v = input[[x, y]];
-                if v > max {
-                    visible_set.insert((x, y));
-                    max = v;
-                }
-            }
+impl Analysis {
+    pub fn new(trees: &Grid<u8>) -> Self {
+        Analysis {
+            visible: Grid::from_fn(trees.rows(), trees.cols(), |pos| is_visible(trees, pos)),
+            scenic: Grid::from_fn(trees.rows(), trees.cols(), |pos| scenic_score(trees, pos)),
         }
+    }
 
-        for y in 1..(shape[1] - 1) {
-            let mut max = input[[0, y]];
-            for x in 1..(shape[0] - 1) {
-                if max == 9 {
-                    break;
-                }
-                let v = input[[x, y]];
-                if v > max {
-                    visible_set.insert((x, y));
-                    max = v;
-                }
-            }
+    pub fn visible_count(&self) -> usize {
+        self.visible.values().filter(|v| **v).count()
+    }
 
-            max = input[[shape[0] - 1, y]];
-            for x in (1..(shape[0] - 1)).rev() {
-                if max == 9 {
-                    break;
-                }
-                let v = input[[x, y]];
-                if v > max {
-                    visible_set.insert((x, y));
-                    max = v;
-                }
+    /// The `k` trees with the highest scenic score, best first.
+    pub fn top_scenic(&self, k: usize) -> Vec<(Pos, usize)> {
+        self.scenic
+            .iter()
+            .map(|(pos, score)| (pos, *score))
+            .sorted_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)))
+            .take(k)
+            .collect()
+    }
+
+    /// Scenic scores scaled to 0-255 on a log scale, as most of them are
+    /// tiny next to the best one.
+    fn levels(&self) -> Grid<u8> {
+        let max = (*self.scenic.values().max().unwrap_or(&0) as f64).ln_1p();
+        self.scenic.map(|score| {
+            if max == 0.0 {
+                0
+            } else {
+                ((*score as f64).ln_1p() / max * 255.0).round() as u8
             }
+        })
+    }
+
+    /// Binary PGM (P5) with the scenic heatmap.
+    pub fn heatmap_pgm(&self) -> Vec<u8> {
+        let (rows, cols) = (self.scenic.rows(), self.scenic.cols());
+        let mut out = format!("P5\n{cols} {rows}\n255\n").into_bytes();
+        out.extend(self.levels().values());
+        out
+    }
+
+    /// Binary PPM (P6) with the scenic heatmap going from blue to red, and
+    /// trees hidden from outside the grid dimmed.
+    pub fn heatmap_ppm(&self) -> Vec<u8> {
+        let (rows, cols) = (self.scenic.rows(), self.scenic.cols());
+        let mut out = format!("P6\n{cols} {rows}\n255\n").into_bytes();
+        for (level, visible) in self.levels().values().zip(self.visible.values()) {
+            let dim = if *visible { 1 } else { 2 };
+            out.extend([level / dim, 0, (255 - level) / dim]);
         }
+        out
+    }
+}
 
-        // println!(
-        //     "{:?}, {:?}",
-        //     visible_set
-        //         .iter()
-        //         .map(|(x, y)| (x, y, input[[*x, *y]]))
-        //         .collect_vec(),
-        //     shape
-        // );
+impl Solver for Problem {
+    type Input = Grid<u8>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
+        let text = file_reader.lines().map(|x| x.unwrap()).join("\n");
 
+        Grid::parse(&text, |_, c| match c.to_digit(10) {
+            Some(height) => Ok(height as u8),
+            None => Err(format!("wrong tree height {c}")),
+        })
+        .unwrap()
+    }
+
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
         // 1808 1814
-        Ok(visible_set.len() + 2 * (shape[0] - 2) + 2 * (shape[1] - 2) + 4)
+        Ok(input
+            .iter()
+            .filter(|(pos, _)| is_visible(input, *pos))
+            .count())
     }
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
-        let shape = input.shape();
-
-        let r = input
-            .indexed_iter()
-            .map(|((x, y), v)| {
-                let mut up = 0;
-                for i in (0..y).rev() {
-                    if input[[x, i]] <= *v {
-                        up = up + 1;
-                    }
-                    if input[[x, i]] >= *v {
-                        break;
-                    }
-                }
+        Ok(input
+            .iter()
+            .map(|(pos, _)| scenic_score(input, pos))
+            .max()
+            .unwrap_or(0))
+    }
 
-                let mut down = 0;
-                for i in (y + 1)..shape[1] {
-                    if input[[x, i]] <= *v {
-                        down = down + 1;
-                    }
-                    if input[[x, i]] >= *v {
-                        break;
-                    }
-                }
+    /// `visible` draws the trees seen from outside, `top [k]` lists the best
+    /// scenic spots, `view <row> <col>` draws what that tree can see and
+    /// `heatmap <file.pgm|file.ppm>` saves the scenic heatmap.
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        let analysis = Analysis::new(input);
 
-                let mut left = 0;
-                for i in (0..x).rev() {
-                    if input[[i, y]] <= *v {
-                        left = left + 1;
-                    }
-                    if input[[i, y]] >= *v {
-                        break;
-                    }
+        match args.iter().map(|a| a.as_str()).collect_vec().as_slice() {
+            ["visible"] => {
+                println!("{}", analysis.visible.map(|v| if *v { '#' } else { '.' }));
+                println!("{} visible trees", analysis.visible_count());
+            }
+            ["top"] | ["top", _] => {
+                let k = args.get(1).map_or(Ok(10), |v| number(v))?;
+                for ((row, col), score) in analysis.top_scenic(k) {
+                    println!("{row},{col} (height {}): {score}", input[(row, col)]);
                 }
-
-                let mut right = 0;
-                for i in (x + 1)..shape[0] {
-                    if input[[i, y]] <= *v {
-                        right = right + 1;
-                    }
-                    if input[[i, y]] >= *v {
-                        break;
-                    }
+            }
+            ["view", _, _] => {
+                let pos = (number(&args[1])?, number(&args[2])?);
+                if input.get(pos).is_none() {
+                    return Err(format!("{},{} is outside the grid", pos.0, pos.1));
                 }
 
-                up * down * left * right
-            })
-            .max()
-            .unwrap();
+                let seen = ORTHOGONAL
+                    .iter()
+                    .flat_map(|direction| viewing(input, pos, *direction))
+                    .collect_vec();
+                let view = Grid::from_fn(input.rows(), input.cols(), |p| match p {
+                    p if p == pos => 'X',
+                    p if seen.contains(&p) => (b'0' + input[p]) as char,
+                    _ => '.',
+                });
+                println!("{view}");
+                println!(
+                    "{} trees seen, scenic score {}",
+                    seen.len(),
+                    analysis.scenic[pos]
+                );
+            }
+            ["heatmap", file] => {
+                let image = match file.rsplit_once('.') {
+                    Some((_, "pgm")) => analysis.heatmap_pgm(),
+                    Some((_, "ppm")) => analysis.heatmap_ppm(),
+                    _ => return Err(format!("{file} should end in .pgm or .ppm")),
+                };
+                std::fs::write(file, image).map_err(|e| format!("{e}"))?;
+            }
+            _ => {
+                return Err(
+                    "usage: visible | top [k] | view <row> <col> | heatmap <file>".to_owned(),
+                )
+            }
+        }
 
-        Ok(r)
+        Ok(())
     }
 }
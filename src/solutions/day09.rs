@@ -1,7 +1,9 @@
 use itertools::Itertools;
 
+use super::solver::{number, split_flags};
 use super::Solver;
 use std::collections::HashSet;
+use std::fmt::Display;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::num::ParseIntError;
@@ -42,103 +44,172 @@ impl FromStr for Operation {
     }
 }
 
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = match self.direction {
+            Direction::Up => "U",
+            Direction::Down => "D",
+            Direction::Left => "L",
+            Direction::Right => "R",
+        };
+        write!(f, "{direction} {}", self.amount)
+    }
+}
+
+impl Direction {
+    fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+}
+
+/// A rope of any number of knots. `knots[0]` is the head. The tail keeps
+/// track of the cells it has been on, and so does every other knot when
+/// asked for.
 pub struct State {
-    visited: HashSet<(isize, isize)>,
     knots: Vec<(isize, isize)>,
+    /// Cells visited by each knot from `first_tracked` on.
+    visited: Vec<HashSet<(isize, isize)>>,
+    first_tracked: usize,
 }
 
 impl State {
-    fn new(len: usize) -> Self {
-        let mut visited = HashSet::new();
-        visited.insert((0, 0));
+    fn new(len: usize, every_knot: bool) -> Self {
+        let knots = vec![(0, 0); len.max(1)];
+        let first_tracked = if every_knot { 0 } else { knots.len() - 1 };
+        let visited = vec![HashSet::from([(0, 0)]); knots.len() - first_tracked];
+
+        State {
+            knots,
+            visited,
+            first_tracked,
+        }
+    }
 
-        let knots = vec![(0, 0); len];
+    fn visit(&mut self, knot: usize) {
+        if let Some(tracked) = knot.checked_sub(self.first_tracked) {
+            self.visited[tracked].insert(self.knots[knot]);
+        }
+    }
 
-        State { visited, knots }
+    /// Cells `knot` has been on, if it's tracked.
+    fn visited(&self, knot: usize) -> Option<&HashSet<(isize, isize)>> {
+        self.visited.get(knot.checked_sub(self.first_tracked)?)
     }
 
     fn move_head(&mut self, op: &Operation) {
-        // println!("");
-        // println!("{:?}", op);
-
         for _ in 0..op.amount {
             self.move_one(&op.direction);
         }
     }
     fn move_one(&mut self, direction: &Direction) {
-        let last_pos = self.knots.len() - 1;
-        let head = self.knots[last_pos];
-        self.knots[last_pos] = match direction {
-            Direction::Up => (head.0, head.1 - 1),
-            Direction::Down => (head.0, head.1 + 1),
-            Direction::Left => (head.0 - 1, head.1),
-            Direction::Right => (head.0 + 1, head.1),
-        };
-
-        self.equalize(last_pos - 1);
+        let head = self.knots[0];
+        let delta = direction.delta();
+        self.move_head_to((head.0 + delta.0, head.1 + delta.1));
     }
-    fn equalize(&mut self, pos: usize) {
-        let head = self.knots[pos + 1];
-        let tail = self.knots[pos];
 
-        let diff = (head.0 - tail.0, head.1 - tail.1);
-        if diff.0.abs() <= 1 && diff.1.abs() <= 1 {
-            return;
+    /// Moves the head to any cell, one step at a time (diagonally if needed)
+    /// so the rest of the rope can follow.
+    fn move_head_to(&mut self, target: (isize, isize)) {
+        while self.knots[0] != target {
+            let head = self.knots[0];
+            self.knots[0] = (
+                head.0 + (target.0 - head.0).signum(),
+                head.1 + (target.1 - head.1).signum(),
+            );
+            self.visit(0);
+            self.follow();
         }
+    }
 
-        // self.print();
-        // println!("");
-
-        match diff {
-            (2, 0) => self.move_tail(pos, (1, 0)),
-            (-2, 0) => self.move_tail(pos, (-1, 0)),
-            (0, 2) => self.move_tail(pos, (0, 1)),
-            (0, -2) => self.move_tail(pos, (0, -1)),
-            (2, 2) => self.move_tail(pos, (1, 1)),
-            (2, -2) => self.move_tail(pos, (1, -1)),
-            (-2, 2) => self.move_tail(pos, (-1, 1)),
-            (-2, -2) => self.move_tail(pos, (-1, -1)),
-            (2, y) => self.move_tail(pos, (1, y)),
-            (-2, y) => self.move_tail(pos, (-1, y)),
-            (x, 2) => self.move_tail(pos, (x, 1)),
-            (x, -2) => self.move_tail(pos, (x, -1)),
-            x => panic!("diff not seen {:?}", x),
-        }
+    fn follow(&mut self) {
+        for pos in 1..self.knots.len() {
+            let head = self.knots[pos - 1];
+            let tail = self.knots[pos];
 
-        if pos > 0 {
-            self.equalize(pos - 1);
+            let diff = (head.0 - tail.0, head.1 - tail.1);
+            if diff.0.abs() <= 1 && diff.1.abs() <= 1 {
+                return;
+            }
+
+            self.knots[pos] = (tail.0 + diff.0.signum(), tail.1 + diff.1.signum());
+            self.visit(pos);
         }
     }
-    fn move_tail(&mut self, pos: usize, delta: (isize, isize)) {
-        let tail = self.knots[pos];
 
-        self.knots[pos] = (tail.0 + delta.0, tail.1 + delta.1);
+    fn tail_visited(&self) -> usize {
+        self.visited.last().unwrap().len()
+    }
 
-        if pos == 0 {
-            self.visited.insert(self.knots[pos].clone());
+    /// How the puzzle names each knot: `H`, then `T` for a two-knot rope or
+    /// numbers for longer ones.
+    fn label(&self, knot: usize) -> char {
+        match knot {
+            0 => 'H',
+            1 if self.knots.len() == 2 => 'T',
+            i => std::char::from_digit(i as u32, 36).unwrap_or('?'),
         }
     }
 
-    fn _print(&self) {
-        let min_x = self.knots.iter().map(|x| x.0).min().unwrap();
-        let max_x = self.knots.iter().map(|x| x.0).max().unwrap();
-        let min_y = self.knots.iter().map(|x| x.1).min().unwrap();
-        let max_y = self.knots.iter().map(|x| x.1).max().unwrap();
+    /// Draws the rope inside `bounds` ((min_x, min_y), (max_x, max_y)), with
+    /// the knots closer to the head on top and `s` for the start.
+    fn render(&self, bounds: Bounds) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = bounds;
+        (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| match self.knots.iter().position(|k| *k == (x, y)) {
+                        Some(knot) => self.label(knot),
+                        None if (x, y) == (0, 0) => 's',
+                        None => '.',
+                    })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
 
-        let mut lines = (min_y..max_y + 1)
-            .map(|_| (min_x..max_x + 1).map(|_| ".").join(""))
-            .collect_vec();
+    /// Draws the cells visited by `knot`, which has to be tracked.
+    fn render_visited(&self, knot: usize, bounds: Bounds) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = bounds;
+        let visited = self.visited(knot).unwrap();
+        (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| match (x, y) {
+                        (0, 0) => 's',
+                        p if visited.contains(&p) => '#',
+                        _ => '.',
+                    })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+}
 
-        for (i, knot) in self.knots.iter().enumerate() {
-            lines[(knot.1 - min_y) as usize].replace_range(
-                (knot.0 - min_x) as usize..(knot.0 - min_x + 1) as usize,
-                i.to_string().as_str(),
-            );
-        }
+type Bounds = ((isize, isize), (isize, isize));
 
-        let result = lines.join("\n");
-        println!("{result}");
+/// Smallest area that holds the rope during the whole simulation.
+fn simulation_bounds(input: &[Operation], len: usize) -> Bounds {
+    let mut state = State::new(len, true);
+    for op in input {
+        state.move_head(op);
     }
+
+    let cells = state.visited.iter().flatten().collect_vec();
+    (
+        (
+            cells.iter().map(|c| c.0).min().unwrap(),
+            cells.iter().map(|c| c.1).min().unwrap(),
+        ),
+        (
+            cells.iter().map(|c| c.0).max().unwrap(),
+            cells.iter().map(|c| c.1).max().unwrap(),
+        ),
+    )
 }
 
 impl Solver for Problem {
@@ -156,22 +227,87 @@ impl Solver for Problem {
     }
 
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
-        let mut state = State::new(2);
+        let mut state = State::new(2, false);
 
-        for op in input.into_iter() {
+        for op in input.iter() {
             state.move_head(op);
         }
 
-        Ok(state.visited.len())
+        Ok(state.tail_visited())
     }
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
-        let mut state = State::new(10);
+        let mut state = State::new(10, false);
 
-        for op in input.into_iter() {
+        for op in input.iter() {
             state.move_head(op);
         }
 
-        Ok(state.visited.len())
+        Ok(state.tail_visited())
+    }
+
+    /// `visited [knots]` draws the cells each knot went through.
+    /// `replay [knots] [--per-move]` draws the rope after every step, or
+    /// after every move. Ropes have 10 knots by default.
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        let (command, rest) = args
+            .split_first()
+            .ok_or_else(|| "usage: visited|replay [knots] [--per-move]".to_owned())?;
+        let mut per_move = false;
+        let rest = split_flags(rest, |flag| {
+            match flag.name() {
+                "--per-move" => per_move = true,
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        let len = rest.first().map_or(Ok(10), |v| number(v))?;
+        if len == 0 {
+            return Err("a rope needs at least one knot".to_owned());
+        }
+
+        let bounds = simulation_bounds(input, len);
+        let mut state = State::new(len, command == "visited");
+        match command.as_str() {
+            "visited" => {
+                for op in input {
+                    state.move_head(op);
+                }
+                for knot in 0..len {
+                    println!(
+                        "== {} ({} cells) ==",
+                        state.label(knot),
+                        state.visited(knot).unwrap().len()
+                    );
+                    println!();
+                    println!("{}", state.render_visited(knot, bounds));
+                    println!();
+                }
+            }
+            "replay" => {
+                println!("== Initial State ==");
+                println!();
+                println!("{}", state.render(bounds));
+                println!();
+                for op in input {
+                    println!("== {op} ==");
+                    println!();
+                    for _ in 0..op.amount {
+                        state.move_one(&op.direction);
+                        if !per_move {
+                            println!("{}", state.render(bounds));
+                            println!();
+                        }
+                    }
+                    if per_move {
+                        println!("{}", state.render(bounds));
+                        println!();
+                    }
+                }
+            }
+            _ => return Err(format!("unknown command {command}")),
+        }
+
+        Ok(())
     }
 }
@@ -1,63 +1,569 @@
 use itertools::Itertools;
+use num_bigint::BigUint;
 
+use super::solver::number;
 use super::Solver;
+use std::cmp::Ordering;
+use std::fmt::Display;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
 use std::vec;
 
 pub struct Problem;
 
+/// Arithmetic over the `old` worry level, as written after `new = `.
+#[derive(Debug, Clone)]
+enum Expr {
+    Old,
+    Const(usize),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars().peekable();
+        let expr = parse_sum(&mut chars)?;
+        skip_spaces(&mut chars);
+        match chars.next() {
+            None => Ok(expr),
+            Some(c) => Err(format!("unexpected '{c}' in operation '{s}'")),
+        }
+    }
+}
+
+fn skip_spaces(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn parse_sum(chars: &mut Peekable<Chars>) -> Result<Expr, String> {
+    let mut expr = parse_product(chars)?;
+    loop {
+        skip_spaces(chars);
+        match chars.next_if(|c| *c == '+' || *c == '-') {
+            Some('+') => expr = Expr::Add(Box::new(expr), Box::new(parse_product(chars)?)),
+            Some(_) => expr = Expr::Sub(Box::new(expr), Box::new(parse_product(chars)?)),
+            None => return Ok(expr),
+        }
+    }
+}
+
+fn parse_product(chars: &mut Peekable<Chars>) -> Result<Expr, String> {
+    let mut expr = parse_term(chars)?;
+    loop {
+        skip_spaces(chars);
+        match chars.next_if(|c| *c == '*' || *c == '/') {
+            Some('*') => expr = Expr::Mul(Box::new(expr), Box::new(parse_term(chars)?)),
+            Some(_) => expr = Expr::Div(Box::new(expr), Box::new(parse_term(chars)?)),
+            None => return Ok(expr),
+        }
+    }
+}
+
+fn parse_term(chars: &mut Peekable<Chars>) -> Result<Expr, String> {
+    skip_spaces(chars);
+    match chars.peek() {
+        Some('(') => {
+            chars.next();
+            let expr = parse_sum(chars)?;
+            skip_spaces(chars);
+            match chars.next() {
+                Some(')') => Ok(expr),
+                _ => Err("missing ')'".to_owned()),
+            }
+        }
+        Some(c) if c.is_ascii_digit() => {
+            let mut digits = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                digits.push(c);
+            }
+            digits.parse().map(Expr::Const).map_err(|e| format!("{e}"))
+        }
+        Some(c) if c.is_alphabetic() => {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_alphanumeric()) {
+                word.push(c);
+            }
+            match word.as_str() {
+                "old" => Ok(Expr::Old),
+                _ => Err(format!("unknown variable '{word}'")),
+            }
+        }
+        Some(c) => Err(format!("unexpected '{c}'")),
+        None => Err("unexpected end of operation".to_owned()),
+    }
+}
+
+impl Expr {
+    fn eval<W: Worry>(&self, old: &W) -> Result<W, String> {
+        match self {
+            Expr::Old => Ok(old.clone()),
+            Expr::Const(v) => Ok(old.lift(*v)),
+            Expr::Add(a, b) => a.eval(old)?.add(&b.eval(old)?),
+            Expr::Sub(a, b) => a.eval(old)?.sub(&b.eval(old)?),
+            Expr::Mul(a, b) => a.eval(old)?.mul(&b.eval(old)?),
+            Expr::Div(a, b) => a.eval(old)?.div(&b.eval(old)?),
+        }
+    }
+}
+
+impl Expr {
+    /// Whether the result modulo any number only depends on `old` modulo
+    /// that number and can't go negative, so only adding and multiplying.
+    fn keeps_modulo(&self) -> bool {
+        match self {
+            Expr::Old | Expr::Const(_) => true,
+            Expr::Sub(..) | Expr::Div(..) => false,
+            Expr::Add(a, b) | Expr::Mul(a, b) => a.keeps_modulo() && b.keeps_modulo(),
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (a, op, b) = match self {
+            Expr::Old => return write!(f, "old"),
+            Expr::Const(v) => return write!(f, "{v}"),
+            Expr::Add(a, b) => (a, "+", b),
+            Expr::Sub(a, b) => (a, "-", b),
+            Expr::Mul(a, b) => (a, "*", b),
+            Expr::Div(a, b) => (a, "/", b),
+        };
+        let wrap = |e: &Expr| match e {
+            Expr::Old | Expr::Const(_) => format!("{e}"),
+            _ => format!("({e})"),
+        };
+        write!(f, "{} {op} {}", wrap(a), wrap(b))
+    }
+}
+
+impl Expr {
+    /// How the walkthrough words this operation, e.g. "is multiplied by 19".
+    fn describe(&self) -> String {
+        let operands = match self {
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) | Expr::Div(a, b) => {
+                Some((&**a, &**b))
+            }
+            _ => None,
+        };
+
+        match (self, operands) {
+            (Expr::Mul(..), Some((Expr::Old, Expr::Old))) => "is multiplied by itself".to_owned(),
+            (Expr::Mul(..), Some((Expr::Old, Expr::Const(v)))) => format!("is multiplied by {v}"),
+            (Expr::Add(..), Some((Expr::Old, Expr::Const(v)))) => format!("increases by {v}"),
+            (Expr::Sub(..), Some((Expr::Old, Expr::Const(v)))) => format!("decreases by {v}"),
+            (Expr::Div(..), Some((Expr::Old, Expr::Const(v)))) => format!("is divided by {v}"),
+            _ => format!("becomes {self}"),
+        }
+    }
+}
+
+/// Condition after `Test: ` that decides which monkey gets the item.
 #[derive(Debug, Clone, Copy)]
-enum Operation {
-    Add(usize),
-    Mul(usize),
-    Square,
+enum Test {
+    DivisibleBy(usize),
+    GreaterThan(usize),
+    LessThan(usize),
+    EqualTo(usize),
+}
+
+impl FromStr for Test {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (condition, value) = s
+            .trim()
+            .rsplit_once(' ')
+            .ok_or_else(|| format!("wrong test '{s}'"))?;
+        let value = value.parse().map_err(|e| format!("{e}"))?;
+
+        match condition {
+            "divisible by" => Ok(Test::DivisibleBy(value)),
+            "greater than" => Ok(Test::GreaterThan(value)),
+            "less than" => Ok(Test::LessThan(value)),
+            "equal to" => Ok(Test::EqualTo(value)),
+            _ => Err(format!("unknown test '{condition}'")),
+        }
+    }
+}
+
+impl Display for Test {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Test::DivisibleBy(v) => write!(f, "divisible by {v}"),
+            Test::GreaterThan(v) => write!(f, "greater than {v}"),
+            Test::LessThan(v) => write!(f, "less than {v}"),
+            Test::EqualTo(v) => write!(f, "equal to {v}"),
+        }
+    }
+}
+
+impl Test {
+    fn check<W: Worry>(&self, worry: &W) -> Result<bool, String> {
+        match self {
+            Test::DivisibleBy(v) => Ok(worry.rem(*v)? == 0),
+            Test::GreaterThan(v) => Ok(worry.compare(*v)? == Ordering::Greater),
+            Test::LessThan(v) => Ok(worry.compare(*v)? == Ordering::Less),
+            Test::EqualTo(v) => Ok(worry.compare(*v)? == Ordering::Equal),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Monkey {
     items: Vec<usize>,
-    operation: Operation,
-    test: usize,
+    operation: Expr,
+    test: Test,
     positive: usize,
     negative: usize,
 }
 
-fn parse_items(items_line: &String) -> Vec<usize> {
+fn parse_items(items_line: &str) -> Vec<usize> {
     let first_split = items_line.split(": ").collect_vec();
     first_split[1]
         .split(", ")
         .map(|x| x.parse().unwrap())
         .collect_vec()
 }
-fn parse_operation(operation_line: &String) -> Operation {
+fn parse_operation(operation_line: &str) -> Expr {
     let first_split = operation_line.split(" = ").collect_vec();
-    let second_split = first_split[1].split(" ").collect_vec();
-
-    match (second_split[1], second_split[2]) {
-        ("*", "old") => Operation::Square,
-        ("*", v) => Operation::Mul(v.parse().unwrap()),
-        ("+", v) => Operation::Add(v.parse().unwrap()),
-        _ => panic!("unknown operation"),
-    }
+    first_split[1].parse().unwrap()
 }
-fn parse_test(test_line: &String) -> usize {
-    let first_split = test_line.split(" by ").collect_vec();
+fn parse_test(test_line: &str) -> Test {
+    let first_split = test_line.split(": ").collect_vec();
     first_split[1].parse().unwrap()
 }
-fn parse_throw(throw_line: &String) -> usize {
+fn parse_throw(throw_line: &str) -> usize {
     let first_split = throw_line.split(" monkey ").collect_vec();
     first_split[1].parse().unwrap()
 }
 
-fn apply_operation(item: usize, operation: Operation) -> usize {
-    match operation {
-        Operation::Square => item * item,
-        Operation::Add(v) => item + v,
-        Operation::Mul(v) => item * v,
+/// How worry levels are kept in check after each inspection.
+#[derive(Debug, Clone, Copy)]
+enum Relief {
+    Divide(usize),
+    Keep,
+}
+
+trait Worry: Sized + Clone + Display {
+    /// Builds a constant of the same kind as `self`.
+    fn lift(&self, value: usize) -> Self;
+    fn add(&self, rhs: &Self) -> Result<Self, String>;
+    fn sub(&self, rhs: &Self) -> Result<Self, String>;
+    fn mul(&self, rhs: &Self) -> Result<Self, String>;
+    fn div(&self, rhs: &Self) -> Result<Self, String>;
+    fn rem(&self, by: usize) -> Result<usize, String>;
+    fn compare(&self, value: usize) -> Result<Ordering, String>;
+}
+
+impl Worry for usize {
+    fn lift(&self, value: usize) -> Self {
+        value
+    }
+
+    fn add(&self, rhs: &Self) -> Result<Self, String> {
+        self.checked_add(*rhs)
+            .ok_or_else(|| format!("worry level overflowed on {self} + {rhs}"))
+    }
+
+    fn sub(&self, rhs: &Self) -> Result<Self, String> {
+        self.checked_sub(*rhs)
+            .ok_or_else(|| format!("worry level went negative on {self} - {rhs}"))
+    }
+
+    fn mul(&self, rhs: &Self) -> Result<Self, String> {
+        self.checked_mul(*rhs)
+            .ok_or_else(|| format!("worry level overflowed on {self} * {rhs}"))
+    }
+
+    fn div(&self, rhs: &Self) -> Result<Self, String> {
+        self.checked_div(*rhs)
+            .ok_or_else(|| format!("division by zero on {self} / {rhs}"))
+    }
+
+    fn rem(&self, by: usize) -> Result<usize, String> {
+        self.checked_rem(by)
+            .ok_or_else(|| "test divisible by 0".to_owned())
+    }
+
+    fn compare(&self, value: usize) -> Result<Ordering, String> {
+        Ok(self.cmp(&value))
+    }
+}
+
+/// Exact worry levels that keep squaring double in size every time, so
+/// past this they would take forever rather than just long.
+const MAX_EXACT_BITS: u64 = 1 << 16;
+
+impl Worry for BigUint {
+    fn lift(&self, value: usize) -> Self {
+        BigUint::from(value)
+    }
+
+    fn add(&self, rhs: &Self) -> Result<Self, String> {
+        Ok(self + rhs)
+    }
+
+    fn sub(&self, rhs: &Self) -> Result<Self, String> {
+        if self < rhs {
+            return Err(format!("worry level went negative on {self} - {rhs}"));
+        }
+        Ok(self - rhs)
+    }
+
+    fn mul(&self, rhs: &Self) -> Result<Self, String> {
+        if self.bits() + rhs.bits() > MAX_EXACT_BITS {
+            return Err(format!(
+                "worry level grew past {MAX_EXACT_BITS} bits, too big to keep exactly"
+            ));
+        }
+        Ok(self * rhs)
+    }
+
+    fn div(&self, rhs: &Self) -> Result<Self, String> {
+        if *rhs == BigUint::from(0u8) {
+            return Err(format!("division by zero on {self} / {rhs}"));
+        }
+        Ok(self / rhs)
+    }
+
+    fn rem(&self, by: usize) -> Result<usize, String> {
+        if by == 0 {
+            return Err("test divisible by 0".to_owned());
+        }
+        Ok((self % by).try_into().unwrap())
+    }
+
+    fn compare(&self, value: usize) -> Result<Ordering, String> {
+        Ok(self.cmp(&BigUint::from(value)))
+    }
+}
+
+/// Worry level kept modulo the product of every divisibility test. It only
+/// decides throws correctly when all tests are divisibility checks and the
+/// operations only add and multiply.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Modular {
+    value: usize,
+    modulus: usize,
+}
+
+impl Display for Modular {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)
     }
 }
 
+impl Modular {
+    fn new(value: usize, modulus: usize) -> Self {
+        Modular {
+            value: value % modulus,
+            modulus,
+        }
+    }
+
+    /// Same modulus, for a value that's already below it.
+    fn reduced(&self, value: usize) -> Self {
+        Modular {
+            value,
+            modulus: self.modulus,
+        }
+    }
+}
+
+impl Worry for Modular {
+    fn lift(&self, value: usize) -> Self {
+        Modular::new(value, self.modulus)
+    }
+
+    fn add(&self, rhs: &Self) -> Result<Self, String> {
+        // Both are below the modulus, so the real sum is below twice that and
+        // taking the modulus off once is enough, even if the sum wrapped
+        let (sum, overflowed) = self.value.overflowing_add(rhs.value);
+        let value = if overflowed || sum >= self.modulus {
+            sum.wrapping_sub(self.modulus)
+        } else {
+            sum
+        };
+        Ok(self.reduced(value))
+    }
+
+    fn sub(&self, _: &Self) -> Result<Self, String> {
+        // Wrapping around would hide the worry level going negative, which
+        // is an error for exact worry levels
+        Err("modular worry levels can't be subtracted".to_owned())
+    }
+
+    fn mul(&self, rhs: &Self) -> Result<Self, String> {
+        let value = match self.value.checked_mul(rhs.value) {
+            Some(value) => value % self.modulus,
+            None => (self.value as u128 * rhs.value as u128 % self.modulus as u128) as usize,
+        };
+        Ok(self.reduced(value))
+    }
+
+    fn div(&self, _: &Self) -> Result<Self, String> {
+        Err("modular worry levels can't be divided".to_owned())
+    }
+
+    fn rem(&self, by: usize) -> Result<usize, String> {
+        // No need to check `by` divides the modulus, as the modulus is built
+        // from every test and this runs for every item thrown.
+        if by == 0 {
+            return Err("test divisible by 0".to_owned());
+        }
+        Ok(self.value % by)
+    }
+
+    fn compare(&self, _: usize) -> Result<Ordering, String> {
+        Err("modular worry levels can't be compared".to_owned())
+    }
+}
+
+/// Everything that happens while the monkeys play, in the order the puzzle
+/// walkthrough describes it.
+enum Event<'a, W> {
+    Turn(usize),
+    Inspect(&'a W),
+    Operation(&'a Expr, &'a W),
+    Relief(usize, &'a W),
+    Test(Test, bool),
+    Throw(&'a W, usize),
+    Round(usize, &'a [Vec<W>]),
+}
+
+impl<W: Display> Display for Event<'_, W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::Turn(m) => write!(f, "Monkey {m}:"),
+            Event::Inspect(worry) => write!(
+                f,
+                "  Monkey inspects an item with a worry level of {worry}."
+            ),
+            Event::Operation(operation, worry) => {
+                write!(f, "    Worry level {} to {worry}.", operation.describe())
+            }
+            Event::Relief(v, worry) => write!(
+                f,
+                "    Monkey gets bored with item. Worry level is divided by {v} to {worry}."
+            ),
+            Event::Test(test, true) => write!(f, "    Current worry level is {test}."),
+            Event::Test(test, false) => write!(f, "    Current worry level is not {test}."),
+            Event::Throw(worry, target) => write!(
+                f,
+                "    Item with worry level {worry} is thrown to monkey {target}."
+            ),
+            Event::Round(round, holdings) => {
+                writeln!(f)?;
+                write!(
+                    f,
+                    "After round {round}, the monkeys are holding items with these worry levels:"
+                )?;
+                for (m, items) in holdings.iter().enumerate() {
+                    write!(f, "\nMonkey {m}: {}", items.iter().join(", "))?;
+                }
+                writeln!(f)
+            }
+        }
+    }
+}
+
+/// Runs the monkeys for `rounds` rounds and returns how many items each one inspected.
+fn simulate<W: Worry>(
+    monkeys: &[Monkey],
+    rounds: usize,
+    relief: Relief,
+    to_worry: impl Fn(usize) -> W,
+    mut log: impl FnMut(Event<W>),
+) -> Result<Vec<usize>, String> {
+    let mut items: Vec<Vec<W>> = monkeys
+        .iter()
+        .map(|m| m.items.iter().map(|item| to_worry(*item)).collect())
+        .collect();
+    let mut inspections = vec![0; monkeys.len()];
+
+    for round in 0..rounds {
+        for m in 0..monkeys.len() {
+            log(Event::Turn(m));
+            let held = std::mem::take(&mut items[m]);
+            inspections[m] += held.len();
+
+            for item in held {
+                log(Event::Inspect(&item));
+                let mut worry = monkeys[m].operation.eval(&item)?;
+                log(Event::Operation(&monkeys[m].operation, &worry));
+                if let Relief::Divide(v) = relief {
+                    worry = worry.div(&worry.lift(v))?;
+                    log(Event::Relief(v, &worry));
+                }
+                let passed = monkeys[m].test.check(&worry)?;
+                log(Event::Test(monkeys[m].test, passed));
+                let target = if passed {
+                    monkeys[m].positive
+                } else {
+                    monkeys[m].negative
+                };
+
+                log(Event::Throw(&worry, target));
+                items[target].push(worry);
+            }
+        }
+        log(Event::Round(round + 1, &items));
+    }
+
+    Ok(inspections)
+}
+
+fn print_event<W: Display>(event: Event<W>) {
+    println!("{event}");
+}
+
+/// Product of every divisibility test, which keeps all of them decidable.
+/// None when a test divides by 0 or the product doesn't fit.
+fn modulus(monkeys: &[Monkey]) -> Option<usize> {
+    monkeys
+        .iter()
+        .filter_map(|m| match m.test {
+            Test::DivisibleBy(v) => Some(v),
+            _ => None,
+        })
+        .try_fold(1, |product: usize, v| {
+            product.checked_mul(v).filter(|p| *p != 0)
+        })
+}
+
+/// Whether worry levels modulo the tests still decide every throw the same
+/// way exact ones do: all tests have to be divisibility checks, and every
+/// operation has to only add and multiply.
+fn modular_fits(monkeys: &[Monkey]) -> bool {
+    monkeys
+        .iter()
+        .all(|m| matches!(m.test, Test::DivisibleBy(_)) && m.operation.keeps_modulo())
+}
+
+/// Runs with `usize` worry levels, going over to exact ones when that
+/// fails. Errors that aren't overflows just come up again.
+fn simulate_exact(monkeys: &[Monkey], rounds: usize, relief: Relief) -> Result<Vec<usize>, String> {
+    simulate(monkeys, rounds, relief, |item| item, |_| {})
+        .or_else(|_| simulate(monkeys, rounds, relief, BigUint::from, |_| {}))
+}
+
+fn monkey_business(mut inspections: Vec<usize>) -> usize {
+    inspections.sort();
+    inspections.into_iter().rev().take(2).product()
+}
+
+/// Rounds `inspect 11 check` runs by default. Exact worry levels grow too
+/// fast to go much further, but a few rounds are enough to check that the
+/// modular reduction doesn't change any throw.
+const CROSS_CHECK_ROUNDS: usize = 20;
+
 impl Solver for Problem {
     type Input = Vec<Monkey>;
     type Output1 = usize;
@@ -91,86 +597,68 @@ impl Solver for Problem {
     }
 
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
-        let mut monkeys = input.clone();
-        let mut inspections = vec![0; input.len()];
-
-        for _ in 0..20 {
-            for m in 0..monkeys.len() {
-                inspections[m] += monkeys[m].items.len();
-
-                for i in 0..monkeys[m].items.len() {
-                    let item = monkeys[m].items[i];
-                    let worry = apply_operation(item, monkeys[m].operation) / 3;
-                    let target = if worry % monkeys[m].test == 0 {
-                        monkeys[m].positive
-                    } else {
-                        monkeys[m].negative
-                    };
-
-                    monkeys[target].items.push(worry);
-                }
-                monkeys[m].items.clear();
-            }
-
-            // println!("After round {}", r + 1);
-            // for m in 0..monkeys.len() {
-            //     println!("Monkey {m}: {:?}", monkeys[m].items);
-            // }
-            // println!("");
-        }
-
-        // println!("{:?}", inspections);
-        inspections.sort();
-
         // 102512
-        Ok(inspections
-            .into_iter()
-            .rev()
-            .take(2)
-            .reduce(|a, b| a * b)
-            .unwrap())
+        Ok(monkey_business(simulate_exact(
+            input,
+            20,
+            Relief::Divide(3),
+        )?))
     }
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
-        let mut monkeys = input.clone();
-        let mut inspections = vec![0; input.len()];
+        let Some(modulus) = modulus(input).filter(|_| modular_fits(input)) else {
+            return Ok(monkey_business(simulate_exact(input, 10000, Relief::Keep)?));
+        };
 
-        let common = monkeys.iter().map(|m| m.test).reduce(|a, b| a * b).unwrap();
-
-        for _ in 0..10000 {
-            for m in 0..monkeys.len() {
-                inspections[m] += monkeys[m].items.len();
+        Ok(monkey_business(simulate(
+            input,
+            10000,
+            Relief::Keep,
+            |item| Modular::new(item, modulus),
+            |_| {},
+        )?))
+    }
 
-                for i in 0..monkeys[m].items.len() {
-                    let item = monkeys[m].items[i];
-                    let worry = apply_operation(item, monkeys[m].operation) % common;
-                    let target = if worry % monkeys[m].test == 0 {
-                        monkeys[m].positive
-                    } else {
-                        monkeys[m].negative
-                    };
+    /// Prints the round-by-round walkthrough: `[rounds] [modular|exact]`,
+    /// where `modular` and `exact` follow part 2's rules instead of dividing
+    /// by 3, keeping worry levels modulo the tests or as they are.
+    /// `check [rounds]` compares both of part 2's modes over a few rounds.
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        let modulus = modulus(input)
+            .ok_or("the tests make no modulus, one divides by 0 or their product overflows");
+        let to_modular = |modulus: usize| move |item: usize| Modular::new(item, modulus);
 
-                    monkeys[target].items.push(worry);
-                }
-                monkeys[m].items.clear();
+        if args.first().is_some_and(|a| a == "check") {
+            let rounds = args.get(1).map_or(Ok(CROSS_CHECK_ROUNDS), |v| number(v))?;
+            let exact = simulate(input, rounds, Relief::Keep, BigUint::from, |_| {})?;
+            let modular = simulate(input, rounds, Relief::Keep, to_modular(modulus?), |_| {})?;
+            if exact != modular {
+                return Err(format!(
+                    "modular reduction diverged from exact worry levels: {exact:?} != {modular:?}"
+                ));
             }
+            println!("both modes agree over {rounds} rounds: {exact:?}");
+            return Ok(());
+        }
 
-            // println!("After round {}", r + 1);
-            // for m in 0..monkeys.len() {
-            //     println!("Monkey {m}: {:?}", monkeys[m].items);
-            // }
-            // println!("");
+        let mut rounds = 1;
+        let mut mode = "";
+        for arg in args {
+            match arg.as_str() {
+                "modular" | "exact" => mode = arg,
+                _ => rounds = number(arg)?,
+            }
         }
 
-        // println!("{:?}", inspections);
-        inspections.sort();
+        match mode {
+            "modular" => {
+                let to_worry = to_modular(modulus?);
+                simulate(input, rounds, Relief::Keep, to_worry, print_event)?
+            }
+            "exact" => simulate(input, rounds, Relief::Keep, BigUint::from, print_event)?,
+            _ => simulate(input, rounds, Relief::Divide(3), |item| item, print_event)?,
+        };
 
-        // 102512
-        Ok(inspections
-            .into_iter()
-            .rev()
-            .take(2)
-            .reduce(|a, b| a * b)
-            .unwrap())
+        Ok(())
     }
 }
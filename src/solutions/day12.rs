@@ -1,17 +1,107 @@
 use itertools::Itertools;
-use ndarray::Array2;
-use pathfinding::prelude::dijkstra;
 
+use super::grid::{Grid, Pos, ORTHOGONAL, SURROUNDING};
+use super::search::{dijkstra, Explored};
+use super::solver::{number, split_flags};
 use super::Solver;
+use std::cell::OnceCell;
+use std::cmp::Ordering::{Equal, Greater, Less};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 pub struct Problem;
 
 pub struct InputStruct {
-    maze: Array2<u8>,
-    start: (usize, usize),
-    end: (usize, usize),
+    maze: Grid<u8>,
+    starts: Vec<Pos>,
+    ends: Vec<Pos>,
+    /// Distances under the puzzle's rules, searched once for both parts.
+    puzzle_distances: OnceCell<Explored<Pos>>,
+}
+
+/// What one step costs.
+#[derive(Debug, Clone, Copy)]
+pub enum StepCost {
+    /// Every step costs 1.
+    Unit,
+    /// 1 plus how much higher the next square is.
+    Climb,
+    /// 1 plus the height of the next square.
+    Height,
+}
+
+/// How the hike can move around the map. The default is the puzzle's.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    max_climb: u8,
+    max_descent: Option<u8>,
+    diagonal: bool,
+    cost: StepCost,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            max_climb: 1,
+            max_descent: None,
+            diagonal: false,
+            cost: StepCost::Unit,
+        }
+    }
+}
+
+impl Rules {
+    /// Splits `args` into rules and the remaining positional arguments.
+    fn parse(args: &[String]) -> Result<(Self, Vec<&String>), String> {
+        let mut rules = Rules::default();
+        let positional = split_flags(args, |flag| {
+            match flag.name() {
+                "--climb" => rules.max_climb = flag.number()?,
+                "--descent" => rules.max_descent = Some(flag.number()?),
+                "--diagonal" => rules.diagonal = true,
+                "--cost" => {
+                    rules.cost = match flag.value()?.as_str() {
+                        "unit" => StepCost::Unit,
+                        "climb" => StepCost::Climb,
+                        "height" => StepCost::Height,
+                        v => return Err(format!("unknown cost {v}, use unit|climb|height")),
+                    }
+                }
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+
+        Ok((rules, positional))
+    }
+
+    /// Cost of stepping from a square of height `from` to one of height
+    /// `to`, if it's allowed.
+    fn step(&self, from: u8, to: u8) -> Option<usize> {
+        if to > from && to - from > self.max_climb {
+            return None;
+        }
+        if self
+            .max_descent
+            .is_some_and(|max| from > to && from - to > max)
+        {
+            return None;
+        }
+
+        Some(match self.cost {
+            StepCost::Unit => 1,
+            StepCost::Climb => 1 + to.saturating_sub(from) as usize,
+            StepCost::Height => 1 + to as usize,
+        })
+    }
+
+    fn moves(&self) -> &'static [(isize, isize)] {
+        if self.diagonal {
+            &SURROUNDING
+        } else {
+            &ORTHOGONAL
+        }
+    }
 }
 
 impl Solver for Problem {
@@ -20,115 +110,172 @@ impl Solver for Problem {
     type Output2 = usize;
 
     fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
-        let mut start = (0, 0);
-        let mut end = (0, 0);
-        let maze_vec = file_reader
-            .lines()
-            .map(|x| x.unwrap())
-            .enumerate()
-            .map(|(line_num, line)| {
-                line.bytes()
-                    .enumerate()
-                    .map(|(col_num, x)| match x {
-                        b'S' => {
-                            start = (line_num, col_num);
-                            0
-                        }
-                        b'E' => {
-                            end = (line_num, col_num);
-                            b'z' - b'a'
-                        }
-                        o => o - b'a',
-                    })
-                    .collect_vec()
-            })
-            .collect_vec();
-
-        let maze =
-            Array2::from_shape_fn((maze_vec.len(), maze_vec[0].len()), |(r, c)| maze_vec[r][c]);
-        InputStruct { maze, start, end }
+        let mut starts = vec![];
+        let mut ends = vec![];
+        let text = file_reader.lines().map(|x| x.unwrap()).join("\n");
+        let maze = Grid::parse(&text, |pos, c| match c {
+            'S' => {
+                starts.push(pos);
+                Ok(0)
+            }
+            'E' => {
+                ends.push(pos);
+                Ok(b'z' - b'a')
+            }
+            'a'..='z' => Ok(c as u8 - b'a'),
+            _ => Err(format!("unknown char {c}")),
+        })
+        .unwrap();
+
+        InputStruct {
+            maze,
+            starts,
+            ends,
+            puzzle_distances: OnceCell::new(),
+        }
     }
 
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
-        let shape = input.maze.shape();
-        // println!("{:?} {:?}", input.start, input.end);
-
-        let path = dijkstra(
-            &input.start,
-            |actual| {
-                let res = adjacent(shape, actual)
-                    .filter(|(r, c)| {
-                        let current = input.maze[[actual.0, actual.1]];
-                        let v = input.maze[[*r, *c]];
-                        return v <= current + 1;
-                    })
-                    .map(|p| (p, 1))
-                    .collect_vec();
-                // println!("{:?} {:?}", actual, res);
-                res
-            },
-            |p| *p == input.end,
-        );
-
-        match path {
-            Some((path, _)) => Ok(path.len() - 1),
-            None => Err("path not found".to_string()),
-        }
+        let distances = input.puzzle_distances();
+        input
+            .closest(distances, input.starts.iter().copied())
+            .map(|(cost, _)| cost)
+            .ok_or_else(|| "path not found".to_string())
     }
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
-        let shape = input.maze.shape();
-
-        let starts = input
-            .maze
-            .indexed_iter()
-            .filter(|(_, v)| **v == 0)
-            .map(|(x, _)| (x, 1))
-            .collect_vec();
-
-        let nil = (usize::MAX, usize::MAX);
-
-        let path = dijkstra(
-            &nil,
-            |actual| {
-                if actual == &nil {
-                    starts.clone()
-                } else {
-                    adjacent(shape, actual)
-                        .filter(|(r, c)| {
-                            let current = input.maze[[actual.0, actual.1]];
-                            let v = input.maze[[*r, *c]];
-                            return v <= current + 1;
+        let distances = input.puzzle_distances();
+        input
+            .closest(distances, input.lowest())
+            .map(|(cost, _)| cost)
+            .ok_or_else(|| "path not found".to_string())
+    }
+
+    /// `route [<row> <col>]` draws the route from the closest S (or the
+    /// given square) to an E, `hike` draws the one from the best lowest
+    /// square, and `distances` prints what each square costs to get to an E.
+    /// All take `--climb <n>`, `--descent <n>`, `--diagonal` and
+    /// `--cost unit|climb|height` to change the rules.
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        let (rules, args) = Rules::parse(args)?;
+        let distances = input.distances(&rules);
+
+        let start = match args.iter().map(|a| a.as_str()).collect_vec().as_slice() {
+            ["route"] => {
+                input
+                    .closest(&distances, input.starts.iter().copied())
+                    .ok_or("no S reaches an E")?
+                    .1
+            }
+            ["route", _, _] => (number(args[1])?, number(args[2])?),
+            ["hike"] => {
+                input
+                    .closest(&distances, input.lowest())
+                    .ok_or("no lowest square reaches an E")?
+                    .1
+            }
+            ["distances"] => {
+                let furthest = input.maze.iter().filter_map(|(p, _)| distances.cost(&p));
+                let width = furthest.max().unwrap_or(0).to_string().len();
+                for row in 0..input.maze.rows() {
+                    let line = (0..input.maze.cols())
+                        .map(|col| match distances.cost(&(row, col)) {
+                            Some(cost) => format!("{cost:>width$}"),
+                            None => format!("{:>width$}", "-"),
                         })
-                        .map(|p| (p, 1))
-                        .collect_vec()
+                        .join(" ");
+                    println!("{line}");
                 }
-            },
-            |p| *p == input.end,
+                println!("{}", distances.stats);
+                return Ok(());
+            }
+            _ => return Err("usage: route [<row> <col>] | hike | distances [rules]".to_owned()),
+        };
+
+        let route = input.route(&distances, start)?;
+        println!("{}", input.render(&route));
+        println!(
+            "{} steps from {},{}, costing {}",
+            route.len() - 1,
+            start.0,
+            start.1,
+            distances.cost(&start).unwrap_or(0)
         );
 
-        match path {
-            Some((path, _)) => Ok(path.len() - 2),
-            None => Err("path not found".to_string()),
-        }
+        Ok(())
     }
 }
 
-fn adjacent(shape: &[usize], position: &(usize, usize)) -> impl Iterator<Item = (usize, usize)> {
-    let max_y = shape[0];
-    let max_x = shape[1];
-    let (y, x) = *position;
-
-    [
-        (Some(x), y.checked_sub(1)),
-        (x.checked_sub(1), Some(y)),
-        (Some(x + 1), Some(y)),
-        (Some(x), Some(y + 1)),
-    ]
-    .into_iter()
-    .filter_map(move |v| match v {
-        (Some(x), Some(y)) if x < max_x && y < max_y => Some((x, y)),
-        _ => None,
-    })
-    .map(move |(x, y)| (y, x))
+impl InputStruct {
+    /// Squares one step up the hill can come from, with what that step
+    /// costs.
+    fn descendable<'a>(
+        &'a self,
+        rules: &'a Rules,
+        to: Pos,
+    ) -> impl Iterator<Item = (Pos, usize)> + 'a {
+        self.maze
+            .neighbours(to, rules.moves())
+            .filter_map(move |p| Some((p, rules.step(self.maze[p], self.maze[to])?)))
+    }
+
+    /// What it costs to get from every square to the closest E, searching
+    /// backwards from all of them.
+    fn distances(&self, rules: &Rules) -> Explored<Pos> {
+        dijkstra(
+            self.ends.iter().copied(),
+            |p| self.descendable(rules, *p).collect_vec(),
+            |_| false,
+        )
+    }
+
+    fn puzzle_distances(&self) -> &Explored<Pos> {
+        self.puzzle_distances
+            .get_or_init(|| self.distances(&Rules::default()))
+    }
+
+    fn lowest(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.maze.iter().filter(|(_, v)| **v == 0).map(|(p, _)| p)
+    }
+
+    /// The cheapest of `starts` to get to an E from, with its cost.
+    fn closest(
+        &self,
+        distances: &Explored<Pos>,
+        starts: impl Iterator<Item = Pos>,
+    ) -> Option<(usize, Pos)> {
+        starts.filter_map(|p| Some((distances.cost(&p)?, p))).min()
+    }
+
+    /// Squares from `start` to an E, both included.
+    fn route(&self, distances: &Explored<Pos>, start: Pos) -> Result<Vec<Pos>, String> {
+        if self.maze.get(start).is_none() {
+            return Err(format!("{},{} is outside the map", start.0, start.1));
+        }
+        let mut route = distances
+            .path_to(&start)
+            .ok_or_else(|| format!("no E can be reached from {},{}", start.0, start.1))?;
+        route.reverse();
+        Ok(route)
+    }
+
+    /// The map with arrows along `route` and the Es, like the puzzle shows
+    /// it.
+    fn render(&self, route: &[Pos]) -> Grid<char> {
+        let mut drawing = Grid::new(self.maze.rows(), self.maze.cols(), '.');
+        for (from, to) in route.iter().tuple_windows() {
+            drawing[*from] = match (to.0.cmp(&from.0), to.1.cmp(&from.1)) {
+                (Less, Equal) => '^',
+                (Greater, Equal) => 'v',
+                (Equal, Less) => '<',
+                (Equal, Greater) => '>',
+                (Less, Greater) | (Greater, Less) => '/',
+                _ => '\\',
+            };
+        }
+        for end in &self.ends {
+            drawing[*end] = 'E';
+        }
+        drawing
+    }
 }
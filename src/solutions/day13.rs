@@ -1,121 +1,192 @@
 use itertools::Itertools;
 
 use super::Solver;
+use serde_json::Value;
 use std::cmp::Ordering;
-use std::fmt::Debug;
+use std::fmt::Display;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::str::FromStr;
 
 pub struct Problem;
 
-pub type List = Vec<ListValue>;
-
+/// A packet or part of one: an integer or a list of values.
+#[derive(Debug, Clone)]
 pub enum ListValue {
     Integer(usize),
-    List(List),
+    List(Vec<ListValue>),
 }
 
-impl Debug for ListValue {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ListValue::Integer(i) => write!(f, "{}", i),
-            ListValue::List(l) => write!(f, "{:?}", l),
+/// Recursive descent over the packet syntax, keeping track of the column
+/// for error messages.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, expected: &str) -> String {
+        match self.bytes.get(self.pos) {
+            Some(c) => format!(
+                "column {}: expected {expected}, found '{}'",
+                self.pos + 1,
+                *c as char
+            ),
+            None => format!("column {}: expected {expected}, found end", self.pos + 1),
+        }
+    }
+
+    fn value(&mut self) -> Result<ListValue, String> {
+        match self.bytes.get(self.pos) {
+            Some(b'[') => self.list(),
+            Some(c) if c.is_ascii_digit() => self.integer(),
+            _ => Err(self.error("'[' or a digit")),
+        }
+    }
+
+    fn integer(&mut self) -> Result<ListValue, String> {
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        // Only ASCII digits in there, so it's valid UTF-8
+        let digits = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap();
+        digits
+            .parse()
+            .map(ListValue::Integer)
+            .map_err(|_| format!("column {}: number {digits} is too big", start + 1))
+    }
+
+    fn list(&mut self) -> Result<ListValue, String> {
+        // Skip the '['
+        self.pos += 1;
+        let mut values = vec![];
+        if self.bytes.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(ListValue::List(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(ListValue::List(values));
+                }
+                _ => return Err(self.error("',' or ']'")),
+            }
         }
     }
 }
 
-fn read_list(str: &str) -> (List, String) {
-    let mut elements: List = vec![];
-    let mut remaining = str.to_string();
-    assert_eq!(&remaining[0..1], "[");
-    remaining = remaining[1..].to_string();
+impl FromStr for ListValue {
+    type Err = String;
 
-    while remaining.len() > 0 && &remaining[0..1] != "]" {
-        if &remaining[0..1] == "[" {
-            let (sub_elements, sub_remaining) = read_list(&remaining);
-            elements.push(ListValue::List(sub_elements));
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            bytes: s.as_bytes(),
+            pos: 0,
+        };
+        let value = parser.value()?;
+        if parser.pos < s.len() {
+            return Err(parser.error("end"));
+        }
+        Ok(value)
+    }
+}
+
+/// Same syntax as the input, so it parses back to the same value.
+impl Display for ListValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListValue::Integer(i) => write!(f, "{i}"),
+            ListValue::List(l) => write!(f, "[{}]", l.iter().join(",")),
+        }
+    }
+}
 
-            remaining = sub_remaining;
-            if remaining.len() > 0 && &remaining[0..1] == "," {
-                remaining = remaining[1..].to_string();
+/// Packet order: integers by value, lists element by element then by
+/// length, and an integer against a list as if it were a list of itself.
+impl Ord for ListValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (ListValue::Integer(l), ListValue::Integer(r)) => l.cmp(r),
+            (ListValue::List(l), ListValue::List(r)) => l.cmp(r),
+            (ListValue::List(l), ListValue::Integer(_)) => {
+                l.as_slice().cmp(std::slice::from_ref(other))
             }
-        } else {
-            let pos_comma = remaining.find(",");
-            let pos_end = remaining.find("]");
-            match (pos_comma, pos_end) {
-                (Some(c), Some(e)) => {
-                    let p = c.min(e);
-                    if p != 0 {
-                        let value: usize = remaining[0..p].parse().unwrap();
-                        elements.push(ListValue::Integer(value));
-                    }
-                    remaining = remaining[(p + 1)..].to_string();
-                    if p == e {
-                        break;
-                    }
-                }
-                (Some(p), _) => {
-                    let value: usize = remaining[0..p].parse().unwrap();
-                    elements.push(ListValue::Integer(value));
-                    remaining = remaining[(p + 1)..].to_string();
-                }
-                (None, Some(p)) => {
-                    if p != 0 {
-                        let value: usize = remaining[0..p].parse().unwrap();
-                        elements.push(ListValue::Integer(value));
-                    }
-                    remaining = remaining[(p + 1)..].to_string();
-                    break;
-                }
-                _ => panic!("no marker found"),
+            (ListValue::Integer(_), ListValue::List(r)) => {
+                std::slice::from_ref(self).cmp(r.as_slice())
             }
         }
     }
+}
+
+impl PartialOrd for ListValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Equality has to agree with the ordering, where 2 and [2] are the same.
+impl PartialEq for ListValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
 
-    if remaining.len() > 0 && &remaining[0..1] == "]" {
-        remaining = remaining[1..].to_string()
+impl Eq for ListValue {}
+
+impl ListValue {
+    pub fn to_json(&self) -> Value {
+        match self {
+            ListValue::Integer(i) => Value::from(*i),
+            ListValue::List(l) => Value::Array(l.iter().map(|v| v.to_json()).collect()),
+        }
     }
 
-    // println!("{str} => {:?} => {remaining}", elements);
-    (elements, remaining)
+    /// Reads arrays and non-negative integers, anything else is an error.
+    pub fn from_json(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Array(values) => values
+                .iter()
+                .map(ListValue::from_json)
+                .collect::<Result<_, _>>()
+                .map(ListValue::List),
+            Value::Number(n) => n
+                .as_u64()
+                .map(|i| ListValue::Integer(i as usize))
+                .ok_or_else(|| format!("{n} is not a packet integer")),
+            _ => Err(format!("{value} is not a packet value")),
+        }
+    }
 }
 
 impl Solver for Problem {
-    type Input = Vec<(List, List)>;
+    type Input = Vec<(ListValue, ListValue)>;
     type Output1 = usize;
     type Output2 = usize;
 
     fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
-        let lines = file_reader.lines().map(|x| x.unwrap()).collect_vec();
-
-        let mut result: Vec<(List, List)> = vec![];
-        let mut line = 0;
-        while line < lines.len() {
-            let (left, _) = read_list(&lines[line]);
-            let (right, _) = read_list(&lines[line + 1]);
-            result.push((left, right));
-
-            line += 3;
-        }
-
-        result
+        file_reader
+            .lines()
+            .map(|x| x.unwrap())
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| {
+                line.parse()
+                    .unwrap_or_else(|e| panic!("line {}, {e}", i + 1))
+            })
+            .tuples()
+            .collect()
     }
 
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
-        // for (left, right) in input {
-        //     println!("{:?} {:?}", left, right);
-        // }
-
         let res: usize = input
             .iter()
             .enumerate()
-            .filter(|(_, (left, right))| {
-                let res = cmp(left, right);
-                if res == Ordering::Equal {
-                    println!("{:?} {:?}", left, right)
-                }
-                res == Ordering::Less
-            })
+            .filter(|(_, (left, right))| left < right)
             .map(|(i, _)| i + 1)
             .sum();
 
@@ -124,48 +195,103 @@ impl Solver for Problem {
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
         // 20196 too low
-        let first = find_divider(input, 2) + 1;
-        let second = find_divider(input, 6) + 2;
-        Ok(first * second)
+        let dividers = ["[[2]]", "[[6]]"].map(|d| d.parse().unwrap());
+        Ok(divider_indices(&sort_packets(input, &dividers))
+            .iter()
+            .product())
     }
-}
-
-fn cmp(left: &List, right: &List) -> Ordering {
-    let left_len = left.len();
-    let right_len = right.len();
-    let min_len = left_len.min(right_len);
 
-    // println!("cmp {:?} {:?}", left, right);
+    /// `json` exports the pairs as JSON, `import <file.json>` reads pairs
+    /// back from JSON and `compare <left> <right>` orders two packets.
+    /// `sort [divider...]` prints all packets in order with the given
+    /// dividers added, and where the dividers ended up.
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        match args.iter().map(|a| a.as_str()).collect_vec().as_slice() {
+            ["json"] => {
+                let pairs = input
+                    .iter()
+                    .map(|(l, r)| Value::Array(vec![l.to_json(), r.to_json()]))
+                    .collect();
+                println!("{}", Value::Array(pairs));
+            }
+            ["import", file] => {
+                let text = std::fs::read_to_string(file).map_err(|e| format!("{e}"))?;
+                let json: Value = serde_json::from_str(&text).map_err(|e| format!("{e}"))?;
+                let pairs = json.as_array().ok_or("expected an array of pairs")?;
+                for pair in pairs {
+                    let Some([left, right]) = pair.as_array().map(|p| p.as_slice()) else {
+                        return Err(format!("{pair} is not a pair"));
+                    };
+                    let (left, right) = (ListValue::from_json(left)?, ListValue::from_json(right)?);
+                    println!("{left}\n{right}\n");
+                }
+            }
+            ["compare", left, right] => {
+                let left: ListValue = left.parse().map_err(|e| format!("left packet, {e}"))?;
+                let right: ListValue = right.parse().map_err(|e| format!("right packet, {e}"))?;
+                let sign = match left.cmp(&right) {
+                    Ordering::Less => "<",
+                    Ordering::Equal => "=",
+                    Ordering::Greater => ">",
+                };
+                println!("{left} {sign} {right}");
+            }
+            ["sort", dividers @ ..] => {
+                let dividers = dividers
+                    .iter()
+                    .map(|d| d.parse().map_err(|e| format!("divider {d}, {e}")))
+                    .collect::<Result<Vec<ListValue>, _>>()?;
+                let sorted = sort_packets(input, &dividers);
+                for (packet, divider) in &sorted {
+                    if *divider {
+                        println!("{packet}  <- divider");
+                    } else {
+                        println!("{packet}");
+                    }
+                }
 
-    for i in 0..min_len {
-        let res = match (&left[i], &right[i]) {
-            (ListValue::Integer(l), ListValue::Integer(r)) => l.cmp(r),
-            (ListValue::List(l), ListValue::List(r)) => cmp(&l, &r),
-            (ListValue::List(l), ListValue::Integer(r)) => cmp(&l, &vec![ListValue::Integer(*r)]),
-            (ListValue::Integer(l), ListValue::List(r)) => cmp(&vec![ListValue::Integer(*l)], &r),
-        };
-        if res != Ordering::Equal {
-            // println!("cmp {:?} {:?} => {:?}", left, right, res);
-            return res;
+                let indices = divider_indices(&sorted);
+                if !indices.is_empty() {
+                    println!();
+                    for index in &indices {
+                        println!("divider {} at {index}", sorted[index - 1].0);
+                    }
+                    println!("decoder key {}", indices.iter().product::<usize>());
+                }
+            }
+            _ => {
+                return Err(
+                    "usage: json | import <file.json> | compare <left> <right> | sort [divider...]"
+                        .to_owned(),
+                )
+            }
         }
-    }
 
-    // println!("cmp {:?} {:?} eq, {left_len} ? {right_len}", left, right);
-    if left_len < right_len {
-        Ordering::Less
-    } else if left_len > right_len {
-        Ordering::Greater
-    } else {
-        Ordering::Equal
+        Ok(())
     }
 }
 
-fn find_divider(packets: &Vec<(List, List)>, divider_num: usize) -> usize {
-    let divider: List = vec![ListValue::List(vec![ListValue::Integer(divider_num)])];
+/// All packets and `dividers` in order, each with whether it's a divider.
+/// Dividers go before the packets they're equal to.
+fn sort_packets<'a>(
+    packets: &'a [(ListValue, ListValue)],
+    dividers: &'a [ListValue],
+) -> Vec<(&'a ListValue, bool)> {
+    let packets = packets.iter().flat_map(|(l, r)| [l, r]);
+    // Stable sort, so the dividers being first keeps them first on ties
+    dividers
+        .iter()
+        .map(|d| (d, true))
+        .chain(packets.map(|p| (p, false)))
+        .sorted_by(|a, b| a.0.cmp(b.0))
+        .collect()
+}
 
-    packets
+/// Indices of the dividers in a sorted list, counting from 1.
+fn divider_indices(sorted: &[(&ListValue, bool)]) -> Vec<usize> {
+    sorted
         .iter()
-        .flat_map(|(l, r)| vec![l, r])
-        .filter(|packet| cmp(packet, &divider) == Ordering::Less)
-        .count()
+        .positions(|(_, divider)| *divider)
+        .map(|i| i + 1)
+        .collect()
 }
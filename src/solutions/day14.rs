@@ -1,6 +1,6 @@
 use itertools::Itertools;
-use ndarray::Array2;
 
+use super::grid::Grid;
 use super::Solver;
 use core::panic;
 use std::fs::File;
@@ -42,7 +42,7 @@ enum CaveElement {
 }
 
 fn put_rocks(
-    dest: &mut Array2<CaveElement>,
+    dest: &mut Grid<CaveElement>,
     from: (usize, usize),
     to: (usize, usize),
     x_offset: usize,
@@ -52,21 +52,21 @@ fn put_rocks(
         let max = from.1.max(to.1);
         let x = from.0 + x_offset;
         for y in min..(max + 1) {
-            dest[[y, x]] = CaveElement::Rock
+            dest[(y, x)] = CaveElement::Rock
         }
     } else if from.1 == to.1 {
         let min = from.0.min(to.0) + x_offset;
         let max = from.0.max(to.0) + x_offset;
         let y = from.1;
         for x in min..(max + 1) {
-            dest[[y, x]] = CaveElement::Rock
+            dest[(y, x)] = CaveElement::Rock
         }
     } else {
         panic!("diagonal line?! {:?} {:?}", from, to);
     }
 }
 
-fn array_from_path(rocks: &Vec<RockPath>) -> (Array2<CaveElement>, usize) {
+fn array_from_path(rocks: &Vec<RockPath>) -> (Grid<CaveElement>, usize) {
     let max_y = rocks
         .iter()
         .map(|x| x.path.iter().map(|(_, y)| y).max().unwrap())
@@ -85,10 +85,7 @@ fn array_from_path(rocks: &Vec<RockPath>) -> (Array2<CaveElement>, usize) {
     let extra_x = 500;
     let start_point = 500 - min_x + extra_x;
 
-    let mut res: Array2<CaveElement> = Array2::from_elem(
-        (max_y + 1 + 2, max_x - min_x + extra_x * 2),
-        CaveElement::Air,
-    );
+    let mut res = Grid::new(max_y + 1 + 2, max_x - min_x + extra_x * 2, CaveElement::Air);
 
     for r in rocks {
         for (from, to) in (0..r.path.len()).tuple_windows() {
@@ -99,34 +96,34 @@ fn array_from_path(rocks: &Vec<RockPath>) -> (Array2<CaveElement>, usize) {
     (res, start_point)
 }
 
-fn put_sand(dest: &mut Array2<CaveElement>, start: usize) -> bool {
-    let shape = dest.shape().to_owned();
+fn put_sand(dest: &mut Grid<CaveElement>, start: usize) -> bool {
+    let rows = dest.rows();
     let mut x = start;
     let mut y = 0;
-    if dest[[y, x]] != CaveElement::Air {
+    if dest[(y, x)] != CaveElement::Air {
         return false;
     }
 
-    while y < shape[0] - 2 {
-        match dest[[y + 1, x]] {
+    while y < rows - 2 {
+        match dest[(y + 1, x)] {
             CaveElement::Air => {
                 y += 1;
             }
             _ => {
-                if dest[[y + 1, x - 1]] == CaveElement::Air {
+                if dest[(y + 1, x - 1)] == CaveElement::Air {
                     y += 1;
                     x -= 1;
-                } else if dest[[y + 1, x + 1]] == CaveElement::Air {
+                } else if dest[(y + 1, x + 1)] == CaveElement::Air {
                     y += 1;
                     x += 1;
                 } else {
-                    dest[[y, x]] = CaveElement::Sand;
+                    dest[(y, x)] = CaveElement::Sand;
                     return true;
                 }
             }
         }
     }
-    dest[[y, x]] = CaveElement::Sand;
+    dest[(y, x)] = CaveElement::Sand;
 
     return true;
 }
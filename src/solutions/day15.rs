@@ -1,3 +1,5 @@
+use super::intervals::IntervalSet;
+use super::solver::number;
 use super::Solver;
 use itertools::Itertools;
 use std::collections::HashSet;
@@ -5,7 +7,6 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::num::ParseIntError;
 use std::str::FromStr;
-use std::vec;
 
 pub struct Problem;
 
@@ -35,6 +36,180 @@ impl FromStr for Detection {
     }
 }
 
+impl Detection {
+    fn radius(&self) -> isize {
+        (self.sensor.0 - self.beacon.0).abs() + (self.sensor.1 - self.beacon.1).abs()
+    }
+
+    fn covers(&self, (x, y): (isize, isize)) -> bool {
+        (self.sensor.0 - x).abs() + (self.sensor.1 - y).abs() <= self.radius()
+    }
+
+    /// What the sensor covers of row `y`.
+    fn row_segment(&self, y: isize) -> Option<(isize, isize)> {
+        // width = 0 -> distance, 1 -> distance-1, 2 -> distance-2, etc.
+        let signal_strength = self.radius() - (self.sensor.1 - y).abs();
+        (signal_strength >= 0).then(|| {
+            (
+                self.sensor.0 - signal_strength,
+                self.sensor.0 + signal_strength,
+            )
+        })
+    }
+
+    /// The lines just outside the sensor's diamond, in rotated coordinates:
+    /// two with constant x + y and two with constant x - y.
+    fn boundary(&self) -> ([isize; 2], [isize; 2]) {
+        let (sx, sy) = self.sensor;
+        let r = self.radius() + 1;
+        ([sx + sy - r, sx + sy + r], [sx - sy - r, sx - sy + r])
+    }
+}
+
+/// Everything the sensors cover of row `y`.
+fn row_coverage(input: &[Detection], y: isize) -> IntervalSet {
+    input.iter().filter_map(|d| d.row_segment(y)).collect()
+}
+
+/// Cells of row `y` (only those in `within`, if given) where there can't
+/// be a beacon: covered by a sensor, and not a beacon we already know of.
+fn covered_count(input: &[Detection], y: isize, within: Option<(isize, isize)>) -> usize {
+    let mut row = row_coverage(input, y);
+    if let Some((start, end)) = within {
+        row = row.intersection(&IntervalSet::range(start, end));
+    }
+    let beacons: HashSet<isize> = input
+        .iter()
+        .filter(|d| d.beacon.1 == y)
+        .map(|d| d.beacon.0)
+        .collect();
+
+    row.len() - beacons.iter().filter(|b| row.contains(**b)).count()
+}
+
+/// Same as `covered_count`, over the rectangle from `min` to `max`.
+fn region_covered_count(input: &[Detection], min: (isize, isize), max: (isize, isize)) -> usize {
+    (min.1..=max.1)
+        .map(|y| covered_count(input, y, Some((min.0, max.0))))
+        .sum()
+}
+
+/// Cells in the rectangle from `min` to `max` that no sensor reaches, row
+/// by row.
+fn uncovered(
+    input: &[Detection],
+    min: (isize, isize),
+    max: (isize, isize),
+) -> impl Iterator<Item = (isize, isize)> + '_ {
+    (min.1..=max.1).flat_map(move |y| {
+        row_coverage(input, y)
+            .gaps(min.0, max.0)
+            .flat_map(move |(start, end)| (start..=end).map(move |x| (x, y)))
+    })
+}
+
+/// Smallest rectangle holding every sensor's diamond.
+fn map_bounds(input: &[Detection]) -> ((isize, isize), (isize, isize)) {
+    let min = |f: fn(&Detection) -> isize| input.iter().map(f).min().unwrap_or(0);
+    let max = |f: fn(&Detection) -> isize| input.iter().map(f).max().unwrap_or(0);
+    (
+        (
+            min(|d| d.sensor.0 - d.radius()),
+            min(|d| d.sensor.1 - d.radius()),
+        ),
+        (
+            max(|d| d.sensor.0 + d.radius()),
+            max(|d| d.sensor.1 + d.radius()),
+        ),
+    )
+}
+
+/// Draws the sensors like the puzzle does, each character standing for a
+/// `scale` by `scale` block: `S` and `B` for sensors and beacons, `#` when
+/// the whole block is covered, `+` when part of it is and `.` otherwise.
+fn render(input: &[Detection], scale: usize) -> String {
+    let (min, max) = map_bounds(input);
+    let scale = scale.max(1) as isize;
+    let block = |start: isize, end: isize| (start..=end).step_by(scale as usize);
+
+    block(min.1, max.1)
+        .map(|y| {
+            let rows = y..(y + scale).min(max.1 + 1);
+            let coverage = rows.clone().map(|y| row_coverage(input, y)).collect_vec();
+            block(min.0, max.0)
+                .map(|x| {
+                    let xs = x..=(x + scale - 1).min(max.0);
+                    let inside = |(px, py): (isize, isize)| xs.contains(&px) && rows.contains(&py);
+                    if input.iter().any(|d| inside(d.sensor)) {
+                        return 'S';
+                    }
+                    if input.iter().any(|d| inside(d.beacon)) {
+                        return 'B';
+                    }
+
+                    let covered: isize = coverage
+                        .iter()
+                        .flat_map(|row| row.ranges())
+                        .map(|(s, e)| (e.min(*xs.end()) - s.max(*xs.start()) + 1).max(0))
+                        .sum();
+                    match covered {
+                        0 => '.',
+                        c if c == (xs.end() - xs.start() + 1) * coverage.len() as isize => '#',
+                        _ => '+',
+                    }
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+/// The only spot in `0..=max` for both coordinates that no sensor covers.
+///
+/// If there's just one, it must be right outside some diamonds, so it's
+/// where two of their boundary lines cross, or where one meets the edge of
+/// the area (or a corner). Only those points are checked, and they're
+/// generated as they go, so no more than the boundary lines is stored.
+fn find_gap(input: &[Detection], max: isize) -> Result<(isize, isize), String> {
+    let (sums, diffs): (Vec<_>, Vec<_>) = input.iter().map(|d| d.boundary()).unzip();
+    let sums = sums.into_iter().flatten().unique().collect_vec();
+    let diffs = diffs.into_iter().flatten().unique().collect_vec();
+
+    let crossings = sums
+        .iter()
+        .cartesian_product(diffs.iter())
+        .filter(|(u, v)| (*u - *v) % 2 == 0)
+        .map(|(u, v)| ((u + v) / 2, (u - v) / 2));
+    let on_edges = sums
+        .iter()
+        .flat_map(|u| [(0, *u), (*u, 0), (max, u - max), (u - max, max)])
+        .chain(
+            diffs
+                .iter()
+                .flat_map(|v| [(0, -v), (*v, 0), (max, max - v), (v + max, max)]),
+        );
+    let corners = [(0, 0), (0, max), (max, 0), (max, max)];
+
+    let gaps: HashSet<(isize, isize)> = crossings
+        .chain(on_edges)
+        .chain(corners)
+        .filter(|(x, y)| (0..=max).contains(x) && (0..=max).contains(y))
+        .filter(|p| !input.iter().any(|d| d.covers(*p)))
+        .collect();
+
+    match gaps.len() {
+        1 => {
+            let (x, y) = gaps.into_iter().next().unwrap();
+            // Double check with the whole row
+            match row_coverage(input, y).gaps(0, max).collect_vec().as_slice() {
+                [gap] if *gap == (x, x) => Ok((x, y)),
+                gaps => Err(format!("row {y} has gaps {gaps:?}, expected only {x}")),
+            }
+        }
+        0 => Err(format!("every spot up to {max} is covered")),
+        n => Err(format!("{n} uncovered spots, expected only one")),
+    }
+}
+
 impl Solver for Problem {
     type Input = Vec<Detection>;
     type Output1 = isize;
@@ -50,111 +225,68 @@ impl Solver for Problem {
     }
 
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
-        let mut row = Row {
-            segments: vec![],
-            beacons: HashSet::new(),
-        };
         let row_pos: isize = 2_000_000;
-
-        for detection in input {
-            let (sx, sy) = detection.sensor;
-            let (bx, by) = detection.beacon;
-            let distance = (sx - bx).abs() + (sy - by).abs();
-            let row_distance = (sy - row_pos).abs();
-            // width = 0 -> distance, 1 -> distance-1, 2 -> distance-2, etc.
-            let signal_strength = distance - row_distance;
-            if signal_strength >= 0 {
-                row.add_segment((sx - signal_strength, sx + signal_strength));
-            }
-            if by == row_pos {
-                row.add_beacon(bx);
-            }
-        }
-
-        Ok(row.get_length())
+        Ok(covered_count(input, row_pos, None) as isize)
     }
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
-        let mut rows = vec![];
         let max = 4_000_000;
-        for _ in 0..(max + 1) {
-            rows.push(Row {
-                segments: vec![],
-                beacons: HashSet::new(),
-            });
-        }
-
-        for detection in input {
-            let (sx, sy) = detection.sensor;
-            let (bx, by) = detection.beacon;
-
-            for row_pos in 0..rows.len() {
-                let distance = (sx - bx).abs() + (sy - by).abs();
-                let row_distance = (sy - row_pos as isize).abs();
-                // width = 0 -> distance, 1 -> distance-1, 2 -> distance-2, etc.
-                let signal_strength = distance - row_distance;
-                if signal_strength >= 0 {
-                    rows[row_pos].add_segment((sx - signal_strength, sx + signal_strength));
-                }
-            }
-        }
-
-        for row_pos in 0..rows.len() {
-            if let Some(x) = rows[row_pos].get_free_pos(max) {
-                // println!("{x}");
-                return Ok(x * 4000000 + row_pos as isize);
-            }
-        }
+        let (x, y) = find_gap(input, max)?;
 
         // not 10229183267339
-        Ok(0)
+        Ok(x * 4000000 + y)
     }
-}
 
-struct Row {
-    beacons: HashSet<isize>,
-    segments: Vec<(isize, isize)>,
-}
+    /// `row <y> [<from> <to>]` counts the cells of a row where there can't
+    /// be a beacon and lists what the sensors cover. `region <x0> <y0> <x1>
+    /// <y1>` does the same for a rectangle and lists the uncovered cells.
+    /// `map [scale]` draws the sensors, best for small inputs.
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        let numbers = args
+            .iter()
+            .skip(1)
+            .map(|v| number(v))
+            .collect::<Result<Vec<isize>, _>>()?;
 
-impl Row {
-    fn add_segment(&mut self, segment: (isize, isize)) {
-        self.segments.push(segment);
-        self.segments.sort_by(|a, b| a.0.cmp(&b.0));
-        let mut new_segments = vec![self.segments[0]];
-
-        for s in self.segments.iter() {
-            let last = new_segments.len() - 1;
-            if new_segments[last].1 >= s.0 {
-                new_segments[last].1 = s.1.max(new_segments[last].1);
-            } else {
-                new_segments.push(*s);
+        match (args.first().map(|a| a.as_str()), numbers.as_slice()) {
+            (Some("row"), [y]) => {
+                println!("{}", row_coverage(input, *y));
+                println!(
+                    "{} cells can't have a beacon",
+                    covered_count(input, *y, None)
+                );
+            }
+            (Some("row"), [y, from, to]) => {
+                let within = IntervalSet::range(*from, *to);
+                println!("{}", row_coverage(input, *y).intersection(&within));
+                println!(
+                    "{} cells can't have a beacon",
+                    covered_count(input, *y, Some((*from, *to)))
+                );
+            }
+            (Some("region"), [x0, y0, x1, y1]) => {
+                let (min, max) = ((*x0, *y0), (*x1, *y1));
+                let mut count = 0;
+                for (x, y) in uncovered(input, min, max) {
+                    println!("{x},{y}");
+                    count += 1;
+                }
+                println!("{count} uncovered cells");
+                println!(
+                    "{} cells can't have a beacon",
+                    region_covered_count(input, min, max)
+                );
+            }
+            (Some("map"), []) => println!("{}", render(input, 1)),
+            (Some("map"), [scale]) if *scale > 0 => println!("{}", render(input, *scale as usize)),
+            _ => {
+                return Err(
+                    "usage: row <y> [<from> <to>] | region <x0> <y0> <x1> <y1> | map [scale]"
+                        .to_owned(),
+                )
             }
         }
 
-        self.segments = new_segments;
-        // println!("add segment {:?} -> {:?}", segment, self.segments);
-    }
-    fn add_beacon(&mut self, x: isize) {
-        self.beacons.insert(x);
-    }
-    fn get_length(&self) -> isize {
-        self.segments
-            .iter()
-            .map(|(start, end)| end - start + 1)
-            .sum::<isize>()
-            - self.beacons.len() as isize
-    }
-    fn get_free_pos(&self, max: isize) -> Option<isize> {
-        // println!("{:?}", self.segments);
-        self.segments
-            .iter()
-            .map(|s| s.to_owned())
-            .find(|(start, end)| 0 < *start && *start < max || 0 < *end && *end < max)
-            .map(|(start, end)| {
-                if 0 < start && start < max {
-                    return start - 1;
-                }
-                return end + 1;
-            })
+        Ok(())
     }
 }
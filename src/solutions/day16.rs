@@ -1,7 +1,7 @@
 use itertools::Itertools;
-use pathfinding::prelude::dijkstra_all;
 use regex::Regex;
 
+use super::search::{branch_and_bound, dijkstra, Bounded};
 use super::Solver;
 use core::panic;
 use std::collections::{HashMap, HashSet};
@@ -57,19 +57,24 @@ fn reduce_nodes(nodes: &HashMap<String, Valve>) -> HashMap<String, ImportantValv
             let name = origin.name.clone();
             let rate = origin.rate;
 
-            let res = dijkstra_all(&name, |p| {
-                nodes
-                    .get(p)
-                    .unwrap()
-                    .edges
-                    .iter()
-                    .map(|e| (e.to_owned(), 1))
-            });
+            let res = dijkstra(
+                [name.clone()],
+                |p| {
+                    nodes
+                        .get(p)
+                        .unwrap()
+                        .edges
+                        .iter()
+                        .map(|e| (e.to_owned(), 1))
+                        .collect_vec()
+                },
+                |_| false,
+            );
 
             let edges: HashMap<String, usize> = important_nodes
                 .iter()
                 .filter(|other| other.name != name)
-                .map(|other| (other.name.clone(), res.get(&other.name).unwrap().1))
+                .map(|other| (other.name.clone(), res.cost(&other.name).unwrap()))
                 .collect();
 
             (name.clone(), ImportantValve { rate, edges })
@@ -77,66 +82,131 @@ fn reduce_nodes(nodes: &HashMap<String, Valve>) -> HashMap<String, ImportantValv
         .collect()
 }
 
-// #[derive(Clone, PartialEq, Eq, Hash)]
-// struct SearchState {
-//     position: String,
-//     visited: HashSet<String>,
-//     release: usize,
-//     time: usize,
-// }
-
-// impl SearchState {
-//     fn new(position: &str) -> Self {
-//         Self {
-//             position: position.to_owned(),
-//             visited: HashSet::new(),
-//             release: 0,
-//             time: 0,
-//         }
-//     }
-// }
-
-#[derive(Debug, PartialOrd)]
-struct Possibility {
-    to: String,
-    release: usize,
+/// Opening valves alone. The release of each valve is counted as soon as
+/// we decide to go open it.
+struct Tour<'a> {
+    nodes: &'a HashMap<String, ImportantValve>,
+    visited: HashSet<String>,
+    position: String,
+    time_left: usize,
+    released: usize,
 }
 
-impl PartialEq for Possibility {
-    fn eq(&self, other: &Self) -> bool {
-        return self.release.eq(&other.release);
+impl Bounded for Tour<'_> {
+    fn successors(&self) -> Vec<Self> {
+        let node = self.nodes.get(&self.position).unwrap();
+
+        node.edges
+            .iter()
+            .filter(|(_, distance)| *distance + 1 < self.time_left)
+            .filter(|(dest, _)| !self.visited.contains(*dest))
+            .map(|(x, distance)| {
+                let sub_time = self.time_left - distance - 1;
+                let other_node = self.nodes.get(x).unwrap();
+
+                let mut visited = self.visited.clone();
+                visited.insert(x.clone());
+                Tour {
+                    nodes: self.nodes,
+                    visited,
+                    position: x.clone(),
+                    time_left: sub_time,
+                    released: self.released + other_node.rate * sub_time,
+                }
+            })
+            .collect()
+    }
+
+    fn value(&self) -> usize {
+        self.released
     }
-}
-impl Eq for Possibility {}
 
-impl Ord for Possibility {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        return self.release.cmp(&other.release);
+    /// As if every closed valve was opened going straight from here.
+    fn bound(&self) -> usize {
+        let node = self.nodes.get(&self.position).unwrap();
+        self.released
+            + node
+                .edges
+                .iter()
+                .filter(|(dest, _)| !self.visited.contains(*dest))
+                .map(|(dest, distance)| {
+                    self.nodes.get(dest).unwrap().rate * self.time_left.saturating_sub(distance + 1)
+                })
+                .sum::<usize>()
     }
 }
 
-fn find_max(
-    nodes: &HashMap<String, ImportantValve>,
-    visited: &HashSet<String>,
-    position: String,
+/// Opening valves with an elephant. Each walker is heading to a valve and
+/// gets there in `timeout` minutes; time moves on until one of them arrives.
+struct Duo<'a> {
+    nodes: &'a HashMap<String, ImportantValve>,
+    visited: HashSet<String>,
+    walkers: [(String, usize); 2],
     time_left: usize,
-) -> usize {
-    let node = nodes.get(&position).unwrap();
-    let mut new_visited = visited.clone();
-    new_visited.insert(position.clone());
+    released: usize,
+}
 
-    node.edges
-        .iter()
-        .filter(|(_, distance)| *distance + 1 < time_left)
-        .filter(|(dest, _)| !visited.contains(*dest))
-        .map(|(x, distance)| {
-            let sub_time = time_left - distance - 1;
-            let other_node = nodes.get(x).unwrap();
-            let released = other_node.rate * sub_time;
-            return find_max(nodes, &new_visited, x.clone(), sub_time) + released;
-        })
-        .max()
-        .unwrap_or(0)
+impl Bounded for Duo<'_> {
+    fn successors(&self) -> Vec<Self> {
+        let Some(free) = self.walkers.iter().position(|(_, timeout)| *timeout == 0) else {
+            panic!("No timeout = 0?")
+        };
+        let other_timeout = self.walkers[1 - free].1;
+        let node = self.nodes.get(&self.walkers[free].0).unwrap();
+
+        node.edges
+            .iter()
+            .filter(|(_, distance)| *distance + 1 < self.time_left)
+            .filter(|(dest, _)| !self.visited.contains(*dest))
+            .map(|(x, distance)| {
+                let timeout = distance + 1;
+                let sub_time = self.time_left - timeout;
+                let other_node = self.nodes.get(x).unwrap();
+                let next_step = timeout.min(other_timeout);
+
+                let mut visited = self.visited.clone();
+                visited.insert(x.clone());
+                let mut walkers = self.walkers.clone();
+                walkers[free] = (x.clone(), timeout - next_step);
+                walkers[1 - free].1 -= next_step;
+
+                Duo {
+                    nodes: self.nodes,
+                    visited,
+                    walkers,
+                    time_left: self.time_left - next_step,
+                    released: self.released + other_node.rate * sub_time,
+                }
+            })
+            .collect()
+    }
+
+    fn value(&self) -> usize {
+        self.released
+    }
+
+    /// As if every closed valve was opened by whoever can get there first
+    /// once they've reached their current valve.
+    fn bound(&self) -> usize {
+        self.released
+            + self
+                .nodes
+                .iter()
+                .filter(|(name, _)| !self.visited.contains(*name))
+                .map(|(name, valve)| {
+                    let best_time = self
+                        .walkers
+                        .iter()
+                        .filter_map(|(position, timeout)| {
+                            let distance = self.nodes.get(position).unwrap().edges.get(name)?;
+                            Some(self.time_left.saturating_sub(timeout + distance + 1))
+                        })
+                        .max()
+                        .unwrap_or(0);
+                    valve.rate * best_time
+                })
+                .sum::<usize>()
+    }
 }
 
 impl Solver for Problem {
@@ -157,114 +227,28 @@ impl Solver for Problem {
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
         let important_nodes = reduce_nodes(&input);
 
-        Ok(find_max(
-            &important_nodes,
-            &HashSet::new(),
-            "AA".to_owned(),
-            30,
-        ))
+        let (best, _) = branch_and_bound(Tour {
+            nodes: &important_nodes,
+            visited: HashSet::from(["AA".to_owned()]),
+            position: "AA".to_owned(),
+            time_left: 30,
+            released: 0,
+        });
+
+        Ok(best)
     }
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
         let important_nodes = reduce_nodes(&input);
 
-        Ok(find_max_2(
-            &important_nodes,
-            &HashSet::new(),
-            "AA".to_owned(),
-            0,
-            "AA".to_owned(),
-            0,
-            26,
-        ))
-    }
-}
+        let (best, _) = branch_and_bound(Duo {
+            nodes: &important_nodes,
+            visited: HashSet::from(["AA".to_owned()]),
+            walkers: [("AA".to_owned(), 0), ("AA".to_owned(), 0)],
+            time_left: 26,
+            released: 0,
+        });
 
-fn find_max_2(
-    nodes: &HashMap<String, ImportantValve>,
-    visited: &HashSet<String>,
-    position_a: String,
-    timeout_a: usize,
-    position_b: String,
-    timeout_b: usize,
-    time_left: usize,
-) -> usize {
-    if timeout_a == 0 {
-        let node = nodes.get(&position_a).unwrap();
-        let all_edges = node
-            .edges
-            .iter()
-            .filter(|(_, distance)| *distance + 1 < time_left)
-            .filter(|(dest, _)| !visited.contains(*dest))
-            .collect_vec();
-
-        let take = if timeout_b == 0 {
-            (all_edges.len() + 1) / 2
-        } else {
-            all_edges.len()
-        };
-
-        all_edges
-            .into_iter()
-            .take(take)
-            .map(|(x, distance)| {
-                if time_left == 26 {
-                    println!("{x} {take}");
-                }
-
-                let timeout_a = distance + 1;
-                let sub_time = time_left - timeout_a;
-                let other_node = nodes.get(x).unwrap();
-                let released = other_node.rate * sub_time;
-
-                let next_step = timeout_a.min(timeout_b);
-
-                let mut new_visited = visited.clone();
-                new_visited.insert(x.clone());
-
-                return find_max_2(
-                    nodes,
-                    &new_visited,
-                    x.clone(),
-                    timeout_a - next_step,
-                    position_b.clone(),
-                    timeout_b - next_step,
-                    time_left - next_step,
-                ) + released;
-            })
-            .max()
-            .unwrap_or(0)
-    } else if timeout_b == 0 {
-        let node = nodes.get(&position_b).unwrap();
-
-        node.edges
-            .iter()
-            .filter(|(_, distance)| *distance + 1 < time_left)
-            .filter(|(dest, _)| !visited.contains(*dest))
-            .map(|(x, distance)| {
-                let timeout_b = distance + 1;
-                let sub_time = time_left - timeout_b;
-                let other_node = nodes.get(x).unwrap();
-                let released = other_node.rate * sub_time;
-
-                let mut new_visited = visited.clone();
-                new_visited.insert(x.clone());
-
-                let next_step = timeout_a.min(timeout_b);
-
-                return find_max_2(
-                    nodes,
-                    &new_visited,
-                    position_a.clone(),
-                    timeout_a - next_step,
-                    x.clone(),
-                    timeout_b - next_step,
-                    time_left - next_step,
-                ) + released;
-            })
-            .max()
-            .unwrap_or(0)
-    } else {
-        panic!("No timeout = 0?")
+        Ok(best)
     }
 }
@@ -1,6 +1,11 @@
+use super::cycle::{find_cycle, Cycle};
+use super::grid::Grid;
+use super::solver::{number, split_flags};
 use super::Solver;
 use itertools::Itertools;
+use std::fmt::Display;
 use std::fs::File;
+use std::hash::Hash;
 use std::io::{BufRead, BufReader};
 
 pub struct Problem;
@@ -11,31 +16,137 @@ pub enum Direction {
     Right,
 }
 
-lazy_static! {
-    #[rustfmt::skip]
-    static ref SHAPES: Vec<Vec<u8>> = vec![
-        vec![0b0011110],
-        vec![
-            0b0001000,
-            0b0011100,
-            0b0001000
-        ],
-        vec![
-            0b0011100, // [0] is the bottom edge!
-            0b0000100,
-            0b0000100,
-        ],
-        vec![
-            0b0010000,
-            0b0010000,
-            0b0010000,
-            0b0010000
-        ],
-        vec![
-            0b0011000,
-            0b0011000
-        ]
-    ];
+/// The puzzle's rocks, in falling order.
+const SHAPES: &str = "\
+####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##";
+
+/// Rock shapes from ASCII art, `#` for rock and `.` or space for air,
+/// separated by blank lines. Short lines are padded with air, and rows or
+/// columns of air around a rock are dropped.
+fn parse_shapes(s: &str) -> Result<Vec<Grid<bool>>, String> {
+    let lines = s.lines().collect_vec();
+    let shapes = lines
+        .split(|line| line.trim().is_empty())
+        .filter(|art| !art.is_empty())
+        .enumerate()
+        .map(|(i, art)| {
+            let art = Grid::parse_padded(&art.join("\n"), false, |_, c| match c {
+                '#' => Ok(true),
+                '.' | ' ' => Ok(false),
+                _ => Err(format!("unknown char {c:?}")),
+            })
+            .map_err(|e| format!("shape {}, {e}", i + 1))?;
+
+            let rock = art
+                .iter()
+                .filter(|(_, v)| **v)
+                .map(|(pos, _)| pos)
+                .collect_vec();
+            let (Some((top, bottom)), Some((left, right))) = (
+                rock.iter().map(|p| p.0).minmax().into_option(),
+                rock.iter().map(|p| p.1).minmax().into_option(),
+            ) else {
+                return Err(format!("shape {} is empty", i + 1));
+            };
+            Ok(Grid::from_fn(
+                bottom - top + 1,
+                right - left + 1,
+                |(row, col)| art[(top + row, left + col)],
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if shapes.is_empty() {
+        return Err("no shapes".to_owned());
+    }
+    Ok(shapes)
+}
+
+/// Shape of the chamber and of the rocks falling into it.
+pub struct Chamber {
+    width: usize,
+    shapes: Vec<Grid<bool>>,
+    /// Free columns between the left wall and a new rock.
+    spawn_left: usize,
+    /// Free rows between the top of the tower and a new rock.
+    spawn_above: usize,
+}
+
+impl Default for Chamber {
+    fn default() -> Self {
+        Chamber {
+            width: 7,
+            shapes: parse_shapes(SHAPES).unwrap(),
+            spawn_left: 2,
+            spawn_above: 3,
+        }
+    }
+}
+
+impl Chamber {
+    /// Splits `args` into the chamber setup and the remaining positional
+    /// arguments.
+    fn parse(args: &[String]) -> Result<(Self, Vec<&String>), String> {
+        let mut chamber = Chamber::default();
+        let positional = split_flags(args, |flag| {
+            match flag.name() {
+                "--width" => chamber.width = flag.number()?,
+                "--left" => chamber.spawn_left = flag.number()?,
+                "--above" => chamber.spawn_above = flag.number()?,
+                "--shapes" => {
+                    let file = flag.value()?;
+                    let text = std::fs::read_to_string(file).map_err(|e| format!("{e}"))?;
+                    chamber.shapes = parse_shapes(&text)?;
+                }
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+
+        if let Some(shape) = chamber
+            .shapes
+            .iter()
+            .find(|s| chamber.spawn_left + s.cols() > chamber.width)
+        {
+            return Err(format!(
+                "a shape {} wide doesn't fit {} columns from the wall of a chamber {} wide",
+                shape.cols(),
+                chamber.spawn_left,
+                chamber.width
+            ));
+        }
+
+        Ok((chamber, positional))
+    }
+}
+
+/// Runs `$f::<R>(args...)` with the smallest row type that fits the
+/// chamber, so the usual 7 wide one gets packed into bytes.
+macro_rules! with_rows {
+    ($chamber:expr, $f:ident($($arg:expr),*)) => {
+        match $chamber.width {
+            0..=8 => $f::<u8>($($arg),*),
+            9..=64 => $f::<u64>($($arg),*),
+            65..=128 => $f::<u128>($($arg),*),
+            _ => $f::<WideRow>($($arg),*),
+        }
+    };
 }
 
 impl Solver for Problem {
@@ -63,201 +174,421 @@ impl Solver for Problem {
     }
 
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
-        let mut field = Field::new();
-
-        let mut shape_index = 0;
-        let mut op_index = 0;
-        for _ in 0..2022 {
-            let mut shape = SHAPES[shape_index % SHAPES.len()].clone();
-            shape_index += 1;
-
-            for o in -4.. {
-                let dir = input[op_index % input.len()];
-                op_index += 1;
-
-                let can_move = shape.iter().all(|v| match dir {
-                    Direction::Left => (v & 0b0100_0000) == 0,
-                    Direction::Right => (v & 0b0000_0001) == 0,
-                });
-
-                let shape_bak = shape.clone();
-                if can_move {
-                    for i in 0..shape.len() {
-                        shape[i] = match dir {
-                            Direction::Left => shape[i] << 1,
-                            Direction::Right => shape[i] >> 1,
-                        };
-                    }
-                }
+        let chamber = Chamber::default();
+        Ok(with_rows!(chamber, height_after(&chamber, input, 2022)))
+    }
 
-                if o >= 0 {
-                    if field.intersects(&shape, o as usize) {
-                        shape = shape_bak;
-                    }
-                }
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
+        let chamber = Chamber::default();
+        Ok(with_rows!(chamber, find_tower_cycle(&chamber, input)).extrapolate(1000000000000))
+    }
 
-                if o >= -1 {
-                    if field.intersects(&shape, (o + 1) as usize) {
-                        field.push_shape(&shape, o);
-                        break;
-                    }
-                }
+    /// `height <rocks>` prints the tower height after that many rocks and
+    /// `cycle` where the tower starts repeating itself, with the top rows it
+    /// repeats from. `top <rocks> [rows]` draws the top of the tower after
+    /// that many rocks, by default down to where rocks can still reach, and `replay <rocks> [rows] [delay ms]` shows every
+    /// move of the first rocks, animated when given a delay.
+    /// All take a chamber set up with `--width <n>`, `--shapes <file>`
+    /// (ASCII art separated by blank lines), `--left <n>` and `--above <n>`.
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        let (chamber, args) = Chamber::parse(args)?;
+
+        match args.iter().map(|a| a.as_str()).collect_vec().as_slice() {
+            ["height", rocks] => {
+                let rocks = number(rocks)?;
+                let cycle = with_rows!(chamber, find_tower_cycle(&chamber, input));
+                println!("{}", cycle.extrapolate(rocks));
+            }
+            ["cycle"] => {
+                let cycle = with_rows!(chamber, find_tower_cycle(&chamber, input));
+                println!(
+                    "repeats every {} rocks after the first {}, growing {} rows each time",
+                    cycle.length,
+                    cycle.start,
+                    cycle.gain()
+                );
+                print!(
+                    "{}",
+                    with_rows!(
+                        chamber,
+                        snapshot(&chamber, input, cycle.start, None)
+                    )
+                );
+            }
+            ["top", rocks, rest @ ..] if rest.len() <= 1 => {
+                let rows = rest.first().map(|r| number(r)).transpose()?;
+                print!(
+                    "{}",
+                    with_rows!(chamber, snapshot(&chamber, input, number(rocks)?, rows))
+                );
+            }
+            ["replay", rocks, rest @ ..] if rest.len() <= 2 => {
+                let rows = rest.first().map_or(Ok(20), |r| number(r))?;
+                let delay = rest.get(1).map(|d| number(d)).transpose()?;
+                with_rows!(chamber, replay(&chamber, input, number(rocks)?, rows, delay));
+            }
+            _ => {
+                return Err(
+                    "usage: height <rocks> | cycle | top <rocks> [rows] | replay <rocks> [rows] [delay] [chamber options]"
+                        .to_owned(),
+                )
             }
-            // field.print();
-            // println!();
         }
 
-        Ok(field.lines.len())
+        Ok(())
     }
+}
 
-    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
-        let mut field = Field::new();
-
-        let mut shape_index = 0;
-        let mut op_index = 0;
-
-        let mut states = vec![];
-        let mut heights = vec![];
-        for i in 0.. {
-            let mut shape = SHAPES[shape_index % SHAPES.len()].clone();
-            shape_index += 1;
-
-            for o in -4.. {
-                let dir = input[op_index % input.len()];
-                op_index += 1;
-
-                let can_move = shape.iter().all(|v| match dir {
-                    Direction::Left => (v & 0b0100_0000) == 0,
-                    Direction::Right => (v & 0b0000_0001) == 0,
-                });
-
-                let shape_bak = shape.clone();
-                if can_move {
-                    for i in 0..shape.len() {
-                        shape[i] = match dir {
-                            Direction::Left => shape[i] << 1,
-                            Direction::Right => shape[i] >> 1,
-                        };
-                    }
-                }
+/// The top `rows` rows of the tower after `rocks` rocks, or the ones rocks
+/// can still reach.
+fn snapshot<R: Row>(
+    chamber: &Chamber,
+    jets: &[Direction],
+    rocks: usize,
+    rows: Option<usize>,
+) -> String {
+    let mut tower = Tower::<R>::new(chamber, jets);
+    for _ in 0..rocks {
+        tower.drop_rock();
+    }
+    let rows = rows.unwrap_or(tower.height() - tower.reachable_floor());
+    tower.render(rows, None)
+}
 
-                if o >= 0 {
-                    if field.intersects(&shape, o as usize) {
-                        shape = shape_bak;
-                    }
+/// Prints the top `rows` rows after every move of the first `rocks` rocks,
+/// one after the other, or redrawn in place every `delay` milliseconds.
+fn replay<R: Row>(
+    chamber: &Chamber,
+    jets: &[Direction],
+    rocks: usize,
+    rows: usize,
+    delay: Option<usize>,
+) {
+    let mut tower = Tower::<R>::new(chamber, jets);
+    for _ in 0..rocks {
+        tower.drop_rock_watched(|tower, event, falling| {
+            let frame = tower.render(rows, falling);
+            match delay {
+                Some(ms) => {
+                    // Clear the screen and go back to the top left corner
+                    print!("\x1b[2J\x1b[H{event}\n{frame}");
+                    std::thread::sleep(std::time::Duration::from_millis(ms as u64));
                 }
+                None => println!("{event}\n{frame}"),
+            }
+        });
+    }
+}
 
-                if o >= -1 {
-                    if field.intersects(&shape, (o + 1) as usize) {
-                        field.push_shape(&shape, o);
-                        break;
-                    }
-                }
+fn height_after<R: Row>(chamber: &Chamber, jets: &[Direction], rocks: usize) -> usize {
+    let mut tower = Tower::<R>::new(chamber, jets);
+    for _ in 0..rocks {
+        tower.drop_rock();
+    }
+    tower.height()
+}
+
+/// Where the tower starts repeating itself, tracking its height.
+fn find_tower_cycle<R: Row + Hash>(chamber: &Chamber, jets: &[Direction]) -> Cycle {
+    find_cycle(
+        &mut Tower::<R>::new(chamber, jets),
+        |tower| tower.drop_rock(),
+        |tower| tower.fingerprint(),
+        |tower| tower.height() as u64,
+    )
+}
+
+/// One row of the chamber as a set of columns, column 0 being against the
+/// left wall.
+trait Row: Clone + Eq + Hash {
+    fn empty(width: usize) -> Self;
+    fn get(&self, col: usize) -> bool;
+    fn set(&mut self, col: usize);
+    fn overlaps(&self, other: &Self) -> bool;
+    fn merge(&mut self, other: &Self);
+    /// Every column moved one step towards the left wall, or the right one.
+    fn shifted(&self, direction: Direction) -> Self;
+}
+
+/// Rows packed in the bits of an integer, column 0 being the lowest bit.
+macro_rules! packed_row {
+    ($($t:ty),*) => {$(
+        impl Row for $t {
+            fn empty(_width: usize) -> Self {
+                0
+            }
+
+            fn get(&self, col: usize) -> bool {
+                self >> col & 1 == 1
+            }
+
+            fn set(&mut self, col: usize) {
+                *self |= 1 << col;
+            }
+
+            fn overlaps(&self, other: &Self) -> bool {
+                self & other != 0
             }
 
-            states.push(State::new(&field, shape_index % SHAPES.len()));
-            heights.push(field.lines.len());
-            if i > 0 && i % 2 == 0 {
-                if states[i] == states[i / 2] {
-                    let cycle_length = i / 2;
-                    let cycle_start = i / 2;
-                    let height_increase = heights[i] - heights[i / 2];
-
-                    let v: u64 = 1000000000000;
-                    let missing_steps = v - cycle_start as u64;
-                    let total_rounds = missing_steps / cycle_length as u64;
-                    let remaining_steps = (missing_steps % cycle_length as u64) as usize;
-
-                    let rounds_height =
-                        heights[i / 2] as u64 + total_rounds * height_increase as u64;
-                    let remaining_steps_height =
-                        heights[i / 2 + remaining_steps] - heights[i / 2] - 1;
-
-                    return Ok(rounds_height + remaining_steps_height as u64);
+            fn merge(&mut self, other: &Self) {
+                *self |= other;
+            }
+
+            fn shifted(&self, direction: Direction) -> Self {
+                match direction {
+                    Direction::Left => self >> 1,
+                    Direction::Right => self << 1,
                 }
             }
         }
-        unreachable!("")
-    }
+    )*};
 }
 
-#[derive(PartialEq, Debug)]
-struct State {
-    lines: Vec<u8>,
-    next_shape: usize,
-}
+packed_row!(u8, u64, u128);
 
-impl State {
-    fn new(field: &Field, next_shape: usize) -> Self {
-        let skip = if field.lines.len() < 10 {
-            0
-        } else {
-            field.lines.len() - 10
-        };
-        Self {
-            next_shape,
-            lines: field.lines.iter().skip(skip).map(|x| *x).collect(),
+/// Row of any width, packed in as many words as it takes.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct WideRow(Vec<u64>);
+
+impl Row for WideRow {
+    fn empty(width: usize) -> Self {
+        WideRow(vec![0; width.div_ceil(64)])
+    }
+
+    fn get(&self, col: usize) -> bool {
+        self.0[col / 64].get(col % 64)
+    }
+
+    fn set(&mut self, col: usize) {
+        self.0[col / 64].set(col % 64);
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        self.0.iter().zip(&other.0).any(|(a, b)| a & b != 0)
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a |= b;
         }
     }
+
+    fn shifted(&self, direction: Direction) -> Self {
+        let words = &self.0;
+        // Each word takes the bit that crosses over from its neighbour
+        WideRow(
+            (0..words.len())
+                .map(|i| match direction {
+                    Direction::Left => words[i] >> 1 | words.get(i + 1).map_or(0, |w| w << 63),
+                    Direction::Right => {
+                        words[i] << 1 | i.checked_sub(1).map_or(0, |j| words[j] >> 63)
+                    }
+                })
+                .collect(),
+        )
+    }
 }
 
-struct Field {
-    /**
-     * lines[0] is the bottom of the pit
-     * lines[len-1] is the top of the pit
-     */
-    lines: Vec<u8>,
+/// The rocks that came to rest so far, and what comes next.
+struct Tower<'a, R> {
+    chamber: &'a Chamber,
+    jets: &'a [Direction],
+    /// Each shape as rows at its spawn column, bottom row first.
+    rocks: Vec<Vec<R>>,
+    /// rows[0] is the bottom of the chamber.
+    rows: Vec<R>,
+    dropped: usize,
+    jet: usize,
 }
 
-impl Field {
-    fn new() -> Self {
-        Field { lines: vec![] }
+impl<'a, R: Row> Tower<'a, R> {
+    fn new(chamber: &'a Chamber, jets: &'a [Direction]) -> Self {
+        let rocks = chamber
+            .shapes
+            .iter()
+            .map(|shape| {
+                (0..shape.rows())
+                    .rev()
+                    .map(|row| {
+                        let mut r = R::empty(chamber.width);
+                        for (col, _) in shape.row(row).iter().enumerate().filter(|(_, v)| **v) {
+                            r.set(chamber.spawn_left + col);
+                        }
+                        r
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Tower {
+            chamber,
+            jets,
+            rocks,
+            rows: vec![],
+            dropped: 0,
+            jet: 0,
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
     }
-    /**
-     * offset=0 means the bottom-most row is aligned with the top of the existing lines
-     * offset=1 means the bottom-most row is 1 below top of the existing lines
-     */
-    fn intersects(&self, shape: &Vec<u8>, offset: usize) -> bool {
-        if offset >= self.lines.len() {
-            return true;
+
+    /// Lowest row a rock could still get to. Every bit of a falling rock
+    /// only moves sideways and down through air, so that's the lowest air
+    /// reachable that way from above the tower.
+    fn reachable_floor(&self) -> usize {
+        let width = self.chamber.width;
+        let mut reached = vec![true; width];
+        for y in (0..self.height()).rev() {
+            let row = &self.rows[y];
+            let mut below = (0..width)
+                .map(|col| reached[col] && !row.get(col))
+                .collect_vec();
+            // Spread sideways both ways through the air in the row
+            for col in 1..width {
+                below[col] |= below[col - 1] && !row.get(col);
+            }
+            for col in (0..width - 1).rev() {
+                below[col] |= below[col + 1] && !row.get(col);
+            }
+
+            if !below.contains(&true) {
+                return y + 1;
+            }
+            reached = below;
         }
-        for o in 0..=offset {
-            if offset - o < shape.len() {
-                let line = shape[offset - o];
-                if self.lines[self.lines.len() - o - 1] & line > 0 {
-                    return true;
-                }
+        0
+    }
+
+    /// What decides how the next rocks fall: which rock and jet come next,
+    /// and the rows of the tower they can still reach.
+    fn fingerprint(&self) -> (usize, usize, Vec<R>) {
+        (
+            self.dropped % self.rocks.len(),
+            self.jet % self.jets.len(),
+            self.rows[self.reachable_floor()..].to_vec(),
+        )
+    }
+
+    /// Whether `rock` with its bottom at row `y` misses the tower.
+    fn fits(&self, rock: &[R], y: usize) -> bool {
+        rock.iter()
+            .enumerate()
+            .all(|(i, r)| self.rows.get(y + i).is_none_or(|row| !row.overlaps(r)))
+    }
+
+    /// `rock` moved by a jet, unless a wall is in the way.
+    fn pushed(&self, rock: &[R], direction: Direction) -> Option<Vec<R>> {
+        let edge = match direction {
+            Direction::Left => 0,
+            Direction::Right => self.chamber.width - 1,
+        };
+        if rock.iter().any(|r| r.get(edge)) {
+            return None;
+        }
+        Some(rock.iter().map(|r| r.shifted(direction)).collect())
+    }
+
+    fn drop_rock(&mut self) {
+        self.drop_rock_watched(|_, _, _| {});
+    }
+
+    /// Drops the next rock, calling `watch` after everything that happens
+    /// to it with the rock and its bottom row while it's still falling.
+    fn drop_rock_watched(&mut self, mut watch: impl FnMut(&Self, Event, Option<(&[R], usize)>)) {
+        let mut rock = self.rocks[self.dropped % self.rocks.len()].clone();
+        let mut y = self.height() + self.chamber.spawn_above;
+        watch(self, Event::Spawned, Some((&rock, y)));
+
+        loop {
+            let direction = self.jets[self.jet % self.jets.len()];
+            self.jet += 1;
+            let pushed = self
+                .pushed(&rock, direction)
+                .filter(|pushed| self.fits(pushed, y));
+            let moved = pushed.is_some();
+            if let Some(pushed) = pushed {
+                rock = pushed;
+            }
+            watch(self, Event::Pushed(direction, moved), Some((&rock, y)));
+
+            if y == 0 || !self.fits(&rock, y - 1) {
+                break;
+            }
+            y -= 1;
+            watch(self, Event::Fell, Some((&rock, y)));
+        }
+
+        for (i, r) in rock.iter().enumerate() {
+            match self.rows.get_mut(y + i) {
+                Some(row) => row.merge(r),
+                None => self.rows.push(r.clone()),
             }
         }
-        return false;
+        self.dropped += 1;
+        watch(self, Event::Rested, None);
     }
 
-    fn push_shape(&mut self, shape: &Vec<u8>, offset: isize) {
-        let initial_len = self.lines.len();
-        for o in 0..shape.len() {
-            let line = shape[o];
-            let self_pos = (initial_len as isize - offset + o as isize - 1) as usize;
-            // println!("{self_pos} {offset} {o}");
-            if self_pos == self.lines.len() {
-                self.lines.push(line);
-            } else if self_pos > self.lines.len() {
-                panic!("self pos out of bounds {self_pos} {}", self.lines.len());
-            } else {
-                self.lines[self_pos] = self.lines[self_pos] | line;
+    /// The top `rows` rows of the chamber as the puzzle draws them, with the
+    /// `falling` rock (and its bottom row) as `@`.
+    fn render(&self, rows: usize, falling: Option<(&[R], usize)>) -> String {
+        let top = falling
+            .map_or(0, |(rock, y)| y + rock.len())
+            .max(self.height());
+        let bottom = top.saturating_sub(rows);
+
+        let grid = Grid::from_fn(top - bottom, self.chamber.width, |(row, col)| {
+            let y = top - row - 1;
+            let in_rock = falling
+                .is_some_and(|(rock, at)| y >= at && rock.get(y - at).is_some_and(|r| r.get(col)));
+            match () {
+                _ if in_rock => '@',
+                _ if self.rows.get(y).is_some_and(|r| r.get(col)) => '#',
+                _ => '.',
             }
+        });
+
+        let mut out = grid
+            .to_string()
+            .lines()
+            .map(|line| format!("|{line}|\n"))
+            .join("");
+        if bottom == 0 {
+            out.push_str(&format!("+{}+\n", "-".repeat(self.chamber.width)));
         }
+        out
     }
+}
 
-    fn _print(&self) {
-        for line in self.lines.iter().rev() {
-            let binary = format!("{line:0>7b}");
-            let transformed = binary
-                .chars()
-                .map(|c| if c == '0' { '.' } else { '#' })
-                .join("");
+/// What just happened to a falling rock.
+#[derive(Clone, Copy)]
+enum Event {
+    Spawned,
+    /// Pushed by a jet, and whether it moved.
+    Pushed(Direction, bool),
+    Fell,
+    Rested,
+}
 
-            println!("|{transformed}|");
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let side = |direction: &Direction| match direction {
+            Direction::Left => "left",
+            Direction::Right => "right",
+        };
+        match self {
+            Event::Spawned => write!(f, "A new rock begins falling:"),
+            Event::Pushed(direction, true) => {
+                write!(f, "Jet of gas pushes rock {}:", side(direction))
+            }
+            Event::Pushed(direction, false) => write!(
+                f,
+                "Jet of gas pushes rock {}, but nothing happens:",
+                side(direction)
+            ),
+            Event::Fell => write!(f, "Rock falls 1 unit:"),
+            Event::Rested => write!(f, "Rock comes to rest:"),
         }
-        println!("+-------+")
     }
 }
@@ -1,5 +1,6 @@
 use itertools::Itertools;
 
+use super::search::flood_fill;
 use super::Solver;
 use std::collections::HashSet;
 use std::fs::File;
@@ -110,31 +111,23 @@ impl Solver for Problem {
             })
             .unwrap();
 
-        let mut visited: HashSet<Droplet> = HashSet::new();
-        let mut visited_candidates: HashSet<Droplet> = HashSet::new();
-        let mut to_visit: Vec<Droplet> = vec![start.clone()];
-
-        while !to_visit.is_empty() {
-            let p = to_visit.pop().unwrap();
-            if visited.contains(&p) {
-                continue;
-            }
-            visited.insert(p.clone());
-            let adjacent = get_adjacent(&p);
-            let consider = if candidates.contains(&p) {
-                visited_candidates.insert(p.clone());
+        // Air reachable from the outside: candidates can reach any air around
+        // them, other air only the candidates next to it
+        let visited = flood_fill([start.clone()], |p| {
+            let adjacent = get_adjacent(p);
+            if candidates.contains(p) {
                 adjacent
-                    .iter()
+                    .into_iter()
                     .filter(|x| !droplets.contains(x))
                     .collect_vec()
             } else {
                 adjacent
-                    .iter()
+                    .into_iter()
                     .filter(|x| candidates.contains(x))
                     .collect_vec()
-            };
-            consider.into_iter().for_each(|c| to_visit.push(c.clone()));
-        }
+            }
+        });
+        let visited_candidates = visited.intersection(&candidates).collect_vec();
 
         let res = visited_candidates
             .iter()
@@ -1,197 +1,272 @@
 use itertools::Itertools;
 use regex::Regex;
 
+use super::search::{beam, branch_and_bound, Bounded, Stats};
+use super::solver::number;
 use super::Solver;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::num::ParseIntError;
+use std::io::{BufReader, Read};
 use std::ops::{Add, Mul, Sub};
 use std::str::FromStr;
 
 pub struct Problem;
 
+/// A robot factory: one robot type per resource, each with its own recipe.
+/// Resources are indexed in the order their robots are declared, and the
+/// first declared robot is the one the factory starts with.
 pub struct Blueprint {
     id: usize,
-    ore: FourTuple,
-    clay: FourTuple,
-    obsidian: FourTuple,
-    geode: FourTuple,
+    resources: Vec<String>,
+    costs: Vec<Resources>,
+    target: usize,
 }
 
 impl FromStr for Blueprint {
-    type Err = ParseIntError;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         lazy_static::lazy_static! {
-            static ref LINE_RGX: Regex = Regex::new(r"Blueprint (\d+): Each ore robot costs (\d+) ore. Each clay robot costs (\d+) ore. Each obsidian robot costs (\d+) ore and (\d+) clay. Each geode robot costs (\d+) ore and (\d+) obsidian.").unwrap();
+            static ref ID_RGX: Regex = Regex::new(r"Blueprint (\d+):").unwrap();
+            static ref ROBOT_RGX: Regex = Regex::new(r"Each (\w+) robot costs ([^.]+)\.").unwrap();
+            static ref COST_RGX: Regex = Regex::new(r"^(\d+) (\w+)$").unwrap();
         }
 
-        match LINE_RGX.captures(s) {
-            None => panic!("wrong line {s}"),
-            Some(captures) => Ok(Blueprint {
-                id: captures[1].parse()?,
-                ore: FourTuple(captures[2].parse()?, 0, 0, 0),
-                clay: FourTuple(captures[3].parse()?, 0, 0, 0),
-                obsidian: FourTuple(captures[4].parse()?, captures[5].parse()?, 0, 0),
-                geode: FourTuple(captures[6].parse()?, 0, captures[7].parse()?, 0),
-            }),
+        let id = match ID_RGX.captures(s) {
+            None => return Err(format!("missing blueprint id in {s}")),
+            Some(captures) => captures[1].parse().map_err(|e| format!("{e}"))?,
+        };
+
+        let recipes = ROBOT_RGX
+            .captures_iter(s)
+            .map(|captures| {
+                let costs = captures[2]
+                    .split(" and ")
+                    .map(|cost| match COST_RGX.captures(cost.trim()) {
+                        None => Err(format!("wrong cost '{cost}' in blueprint {id}")),
+                        Some(c) => c[1]
+                            .parse()
+                            .map(|amount: usize| (c[2].to_owned(), amount))
+                            .map_err(|e| format!("{e} in cost '{cost}' in blueprint {id}")),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((captures[1].to_owned(), costs))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if recipes.is_empty() {
+            return Err(format!("blueprint {id} has no robots"));
+        }
+
+        let resources = recipes.iter().map(|(name, _)| name.clone()).collect_vec();
+        if resources.iter().duplicates().next().is_some() {
+            return Err(format!("blueprint {id} declares a robot twice"));
+        }
+
+        let mut costs = vec![];
+        for (_, recipe) in recipes.iter() {
+            let mut cost = Resources::zero(resources.len());
+            for (name, amount) in recipe {
+                match resources.iter().position(|r| r == name) {
+                    None => {
+                        return Err(format!(
+                            "blueprint {id} needs {name}, but no robot collects it"
+                        ))
+                    }
+                    Some(i) => cost.0[i] += amount,
+                }
+            }
+            costs.push(cost);
         }
+
+        let target = resources
+            .iter()
+            .position(|r| r == "geode")
+            .unwrap_or(resources.len() - 1);
+
+        Ok(Blueprint {
+            id,
+            resources,
+            costs,
+            target,
+        })
     }
 }
 
 impl Blueprint {
     fn get_quality(&self, time: usize) -> usize {
-        let geode = self.get_max_geode(time);
+        let geode = self.get_max_yield(time);
         // println!("## {} {geode} ##", self.id);
         self.id * geode
     }
 
-    fn get_max_geode(&self, time: usize) -> usize {
-        let mut to_visit: Vec<Node> = vec![];
-        to_visit.push(Node {
-            robots: FourTuple(1, 0, 0, 0),
-            resources: FourTuple(0, 0, 0, 0),
-            interests: (true, true, true, true),
-        });
-
-        let max_costs = self
-            .ore
-            .max_cost(self.clay)
-            .max_cost(self.obsidian)
-            .max_cost(self.geode);
-
-        for _ in 0..time {
-            // println!("{}: {i} {}", self.id, to_visit.len());
-            let mut new_to_visit: Vec<Node> = vec![];
-            for node in to_visit.iter() {
-                // println!("{:?}", node);
-
-                let mut new_interests = node.interests.clone();
-
-                let will_have = node.resources + node.robots * time;
-                let will_buy = (
-                    node.interests.0 && will_have.can_buy(self.ore) && node.robots.0 < max_costs.0,
-                    node.interests.1 && will_have.can_buy(self.clay) && node.robots.1 < max_costs.1,
-                    node.interests.2
-                        && will_have.can_buy(self.obsidian)
-                        && node.robots.2 < max_costs.2,
-                    node.interests.3 && will_have.can_buy(self.geode),
-                );
-
-                if node.resources.can_buy(self.ore) && will_buy.0 {
-                    new_interests.0 = false;
-                    let new_robots = node.robots + FourTuple(1, 0, 0, 0);
-                    new_to_visit.push(Node {
-                        robots: new_robots,
-                        resources: node.resources + node.robots - self.ore,
-                        interests: (true, true, true, true),
-                    })
-                }
-                if node.resources.can_buy(self.clay) && will_buy.1 {
-                    new_interests.1 = false;
-                    let new_robots = node.robots + FourTuple(0, 1, 0, 0);
-                    new_to_visit.push(Node {
-                        robots: new_robots,
-                        resources: node.resources + node.robots - self.clay,
-                        interests: (true, true, true, true),
-                    })
-                }
-                if node.resources.can_buy(self.obsidian) && will_buy.2 {
-                    new_interests.2 = false;
-                    let new_robots = node.robots + FourTuple(0, 0, 1, 0);
-                    new_to_visit.push(Node {
-                        robots: new_robots,
-                        resources: node.resources + node.robots - self.obsidian,
-                        interests: (true, true, true, true),
-                    })
-                }
-                if node.resources.can_buy(self.geode) && will_buy.3 {
-                    new_interests.3 = false;
-                    let new_robots = node.robots + FourTuple(0, 0, 0, 1);
-                    new_to_visit.push(Node {
-                        robots: new_robots,
-                        resources: node.resources + node.robots - self.geode,
-                        interests: (true, true, true, true),
-                    })
-                }
+    /// Maximum amount of the target resource (geodes, or the last declared
+    /// resource) that can be collected in `time` minutes.
+    fn get_max_yield(&self, time: usize) -> usize {
+        self.search(time, None).0
+    }
 
-                // If this path won't buy more robots, then stop...
-                if !will_buy.0 && !will_buy.1 && !will_buy.2 && !will_buy.3 {
-                    continue;
-                }
+    /// Best yield and search statistics, exact or with a beam of `width`.
+    fn search(&self, time: usize, width: Option<usize>) -> (usize, Stats) {
+        let kinds = self.resources.len();
+        let plan = Plan {
+            blueprint: self,
+            time,
+            max_costs: self
+                .costs
+                .iter()
+                .fold(Resources::zero(kinds), |acc, cost| acc.max_cost(cost)),
+        };
+        let start = Node {
+            plan: &plan,
+            time_left: time,
+            robots: Resources::zero(kinds).with(0, 1),
+            resources: Resources::zero(kinds),
+            interests: vec![true; kinds],
+        };
 
-                new_to_visit.push(Node {
-                    robots: node.robots,
-                    resources: node.resources + node.robots,
-                    interests: new_interests,
-                })
-            }
-            to_visit = new_to_visit;
+        match width {
+            None => branch_and_bound(start),
+            Some(width) => beam(start, width),
         }
-
-        // to_visit.iter().map(|node| node.resources.3).max().unwrap()
-        to_visit
-            .iter()
-            .sorted_by(|a, b| b.resources.3.cmp(&a.resources.3))
-            .take(1)
-            .map(|node| node.resources.3)
-            .max()
-            .unwrap()
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-struct FourTuple(usize, usize, usize, usize);
+/// Amount of each resource, indexed like `Blueprint::resources`.
+#[derive(Clone, Debug)]
+struct Resources(Vec<usize>);
 
-impl Add for FourTuple {
-    type Output = Self;
+impl Add for &Resources {
+    type Output = Resources;
     fn add(self, rhs: Self) -> Self::Output {
-        FourTuple(
-            self.0 + rhs.0,
-            self.1 + rhs.1,
-            self.2 + rhs.2,
-            self.3 + rhs.3,
+        Resources(
+            self.0
+                .iter()
+                .zip(rhs.0.iter())
+                .map(|(a, b)| a + b)
+                .collect(),
         )
     }
 }
-impl Sub for FourTuple {
-    type Output = Self;
+impl Sub for &Resources {
+    type Output = Resources;
     fn sub(self, rhs: Self) -> Self::Output {
-        FourTuple(
-            self.0 - rhs.0,
-            self.1 - rhs.1,
-            self.2 - rhs.2,
-            self.3 - rhs.3,
+        Resources(
+            self.0
+                .iter()
+                .zip(rhs.0.iter())
+                .map(|(a, b)| a - b)
+                .collect(),
         )
     }
 }
-impl Mul<usize> for FourTuple {
-    type Output = Self;
+impl Mul<usize> for &Resources {
+    type Output = Resources;
     fn mul(self, rhs: usize) -> Self::Output {
-        FourTuple(self.0 * rhs, self.1 * rhs, self.2 * rhs, self.3 * rhs)
+        Resources(self.0.iter().map(|a| a * rhs).collect())
     }
 }
 
-impl FourTuple {
-    fn can_buy(&self, other: FourTuple) -> bool {
-        self.0 >= other.0 && self.1 >= other.1 && self.2 >= other.2 && self.3 >= other.3
+impl Resources {
+    fn zero(kinds: usize) -> Resources {
+        Resources(vec![0; kinds])
+    }
+
+    fn with(&self, kind: usize, amount: usize) -> Resources {
+        let mut res = self.clone();
+        res.0[kind] = amount;
+        res
+    }
+
+    fn can_buy(&self, other: &Resources) -> bool {
+        self.0.iter().zip(other.0.iter()).all(|(a, b)| a >= b)
     }
 
-    fn max_cost(&self, other: FourTuple) -> FourTuple {
-        FourTuple(
-            self.0.max(other.0),
-            self.1.max(other.1),
-            self.2.max(other.2),
-            self.3.max(other.3),
+    fn max_cost(&self, other: &Resources) -> Resources {
+        Resources(
+            self.0
+                .iter()
+                .zip(other.0.iter())
+                .map(|(a, b)| *a.max(b))
+                .collect(),
         )
     }
 }
 
-#[derive(Debug)]
-struct Node {
-    robots: FourTuple,
-    resources: FourTuple,
-    interests: (bool, bool, bool, bool),
+/// What doesn't change while searching a blueprint.
+struct Plan<'a> {
+    blueprint: &'a Blueprint,
+    time: usize,
+    max_costs: Resources,
+}
+
+/// State at the start of a minute, with the robot kinds that are still
+/// worth waiting for.
+struct Node<'a> {
+    plan: &'a Plan<'a>,
+    time_left: usize,
+    robots: Resources,
+    resources: Resources,
+    interests: Vec<bool>,
+}
+
+impl Bounded for Node<'_> {
+    fn successors(&self) -> Vec<Self> {
+        if self.time_left == 0 {
+            return vec![];
+        }
+
+        let blueprint = self.plan.blueprint;
+        let kinds = blueprint.resources.len();
+        let mut next = vec![];
+        let mut new_interests = self.interests.clone();
+
+        let will_have = &self.resources + &(&self.robots * self.plan.time);
+        let will_buy = (0..kinds)
+            .map(|kind| {
+                self.interests[kind]
+                    && will_have.can_buy(&blueprint.costs[kind])
+                    && (kind == blueprint.target
+                        || self.robots.0[kind] < self.plan.max_costs.0[kind])
+            })
+            .collect_vec();
+
+        let produced = &self.resources + &self.robots;
+        for kind in 0..kinds {
+            if will_buy[kind] && self.resources.can_buy(&blueprint.costs[kind]) {
+                new_interests[kind] = false;
+                next.push(Node {
+                    plan: self.plan,
+                    time_left: self.time_left - 1,
+                    robots: self.robots.with(kind, self.robots.0[kind] + 1),
+                    resources: &produced - &blueprint.costs[kind],
+                    interests: vec![true; kinds],
+                })
+            }
+        }
+
+        // If this path won't buy more robots, then stop...
+        if will_buy.iter().any(|b| *b) {
+            next.push(Node {
+                plan: self.plan,
+                time_left: self.time_left - 1,
+                robots: self.robots.clone(),
+                resources: produced,
+                interests: new_interests,
+            })
+        }
+
+        next
+    }
+
+    /// What we end up with by just waiting from now on.
+    fn value(&self) -> usize {
+        let target = self.plan.blueprint.target;
+        self.resources.0[target] + self.robots.0[target] * self.time_left
+    }
+
+    /// As if a new target robot was built every minute.
+    fn bound(&self) -> usize {
+        self.value() + self.time_left * self.time_left.saturating_sub(1) / 2
+    }
 }
 
 impl Solver for Problem {
@@ -199,12 +274,29 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = usize;
 
-    fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
-        file_reader
-            .lines()
-            .map(|x| x.unwrap())
-            .map(|line| line.parse())
-            .map(|x| x.unwrap())
+    fn read_input(&self, mut file_reader: BufReader<&File>) -> Self::Input {
+        lazy_static::lazy_static! {
+            static ref BLUEPRINT_RGX: Regex = Regex::new(r"Blueprint \d+:").unwrap();
+        }
+
+        // Blueprints can span several lines, so split on the headers instead
+        let mut contents = String::new();
+        file_reader.read_to_string(&mut contents).unwrap();
+        let contents = contents.split_whitespace().join(" ");
+
+        let starts = BLUEPRINT_RGX
+            .find_iter(&contents)
+            .map(|m| m.start())
+            .collect_vec();
+        starts
+            .iter()
+            .enumerate()
+            .map(|(i, start)| {
+                let end = starts.get(i + 1).copied().unwrap_or(contents.len());
+                contents[*start..end]
+                    .parse()
+                    .unwrap_or_else(|e| panic!("blueprint {}, {e}", i + 1))
+            })
             .collect()
     }
 
@@ -220,7 +312,22 @@ impl Solver for Problem {
         Ok(input
             .iter()
             .take(3)
-            .map(|blueprint| blueprint.get_max_geode(32))
+            .map(|blueprint| blueprint.get_max_yield(32))
             .product())
     }
+
+    /// Searches every blueprint for `[minutes]` (24 by default) and prints
+    /// the best yield with how many states it took. `[width]` uses a beam
+    /// search of that width instead.
+    fn inspect(&self, input: &Self::Input, args: &[String]) -> Result<(), String> {
+        let time = args.first().map_or(Ok(24), |v| number(v))?;
+        let width = args.get(1).map(|v| number(v)).transpose()?;
+
+        for blueprint in input {
+            let (best, stats) = blueprint.search(time, width);
+            println!("Blueprint {}: {best} ({stats})", blueprint.id);
+        }
+
+        Ok(())
+    }
 }
@@ -1,12 +1,13 @@
 use itertools::Itertools;
 
+use super::grid::Grid;
 use super::Solver;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 pub struct Problem;
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum Tile {
     Open,
     Solid,
@@ -33,18 +34,11 @@ struct Player {
     position: (usize, usize),
 }
 
-type Field = Vec<Vec<Tile>>;
+type Field = Grid<Tile>;
 
 impl Player {
     fn new(field: &Field) -> Self {
-        let position = (
-            0,
-            field[0]
-                .iter()
-                .find_position(|t| **t != Tile::Void)
-                .unwrap()
-                .0,
-        );
+        let position = field.position(|t| *t != Tile::Void).unwrap();
         Self {
             position,
             facing: Direction::Right,
@@ -89,50 +83,18 @@ impl Player {
         };
 
         for _ in 0..quantity {
-            let mut next_row = self.position.0;
+            let mut next = self.position;
             loop {
-                if next_row == 0 && delta.0 < 0 {
-                    next_row = field.len() - 1;
-                    // field
-                    //     .iter()
-                    //     .enumerate()
-                    //     .rev()
-                    //     .find(|(_, row)| {
-                    //         row.len() > self.position.1 && row[self.position.1] != Tile::Void
-                    //     })
-                    //     .unwrap()
-                    //     .0
-                } else {
-                    next_row = (next_row as isize + delta.0) as usize % field.len();
-                };
-
-                if field[next_row].len() > self.position.1
-                    && field[next_row][self.position.1] != Tile::Void
-                {
-                    break;
-                }
-            }
-
-            let mut next_col = self.position.1;
-            loop {
-                if next_col == 0 && delta.1 < 0 {
-                    next_col = field[next_row].len() - 1;
-                } else {
-                    next_col =
-                        (next_col as isize + delta.1) as usize % field[self.position.0].len();
-                };
-                // println!("test b {} {}", self.position.0, next_col);
-                if field[self.position.0].len() > next_col
-                    && field[self.position.0][next_col] != Tile::Void
-                {
+                next = field.wrapping_offset(next, delta);
+                if field[next] != Tile::Void {
                     break;
                 }
             }
 
-            if field[next_row][next_col] == Tile::Solid {
+            if field[next] == Tile::Solid {
                 return;
             }
-            self.position = (next_row, next_col);
+            self.position = next;
         }
     }
     fn advance_2(&mut self, field: &Field, quantity: usize) {
@@ -166,7 +128,7 @@ impl Player {
                 } else {
                     // F wraps to A bottom
                     next_facing = Direction::Up;
-                    (field.len() - 1, (next_unwrapped_pos.1 - 100) as usize)
+                    (field.rows() - 1, (next_unwrapped_pos.1 - 100) as usize)
                 }
             } else if next_unwrapped_pos.0 < 100
                 && next_unwrapped_pos.1 < 50
@@ -235,10 +197,10 @@ impl Player {
                 (next_unwrapped_pos.0 as usize, next_unwrapped_pos.1 as usize)
             };
 
-            if field[next_pos.0][next_pos.1] == Tile::Void {
+            if field[next_pos] == Tile::Void {
                 panic!("out of bounds!");
             }
-            if field[next_pos.0][next_pos.1] == Tile::Solid {
+            if field[next_pos] == Tile::Solid {
                 return;
             }
             self.facing = next_facing;
@@ -254,20 +216,18 @@ impl Solver for Problem {
 
     fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
         let lines = file_reader.lines().map(|x| x.unwrap()).collect_vec();
-        let field = lines
-            .iter()
-            .take(lines.len() - 2)
-            .map(|line| {
-                line.chars()
-                    .map(|c| match c {
-                        ' ' => Tile::Void,
-                        '.' => Tile::Open,
-                        '#' => Tile::Solid,
-                        _ => panic!("unknown char {c}"),
-                    })
-                    .collect_vec()
-            })
-            .collect_vec();
+        let field =
+            Grid::parse_padded(
+                &lines[..lines.len() - 2].join("\n"),
+                Tile::Void,
+                |_, c| match c {
+                    ' ' => Ok(Tile::Void),
+                    '.' => Ok(Tile::Open),
+                    '#' => Ok(Tile::Solid),
+                    _ => Err(format!("unknown char {c}")),
+                },
+            )
+            .unwrap();
 
         let mut instructions = vec![];
         let instr_line = &lines[lines.len() - 1];
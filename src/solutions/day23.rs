@@ -1,29 +1,31 @@
 use itertools::Itertools;
 
+use super::grid::{Point, SparseGrid, SURROUNDING};
 use super::Solver;
 use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 pub struct Problem;
 
+#[derive(Clone)]
+pub struct Elf;
+
+impl Display for Elf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#")
+    }
+}
+
 impl Solver for Problem {
-    type Input = HashSet<(isize, isize)>;
+    type Input = SparseGrid<Elf>;
     type Output1 = usize;
     type Output2 = usize;
 
     fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
-        file_reader
-            .lines()
-            .map(|x| x.unwrap())
-            .enumerate()
-            .flat_map(|(y, line)| {
-                line.char_indices()
-                    .filter(|(_, c)| *c == '#')
-                    .map(|(x, _)| (x as isize, y as isize))
-                    .collect_vec()
-            })
-            .collect()
+        let text = file_reader.lines().map(|x| x.unwrap()).join("\n");
+        SparseGrid::parse(&text, |c| (c == '#').then_some(Elf))
     }
 
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
@@ -33,24 +35,7 @@ impl Solver for Problem {
             field.turn();
         }
 
-        let min = field.elves.iter().fold((isize::MAX, isize::MAX), |acc, v| {
-            (acc.0.min(v.0), acc.1.min(v.1))
-        });
-        let max = field.elves.iter().fold((isize::MIN, isize::MIN), |acc, v| {
-            (acc.0.max(v.0), acc.1.max(v.1))
-        });
-
-        // for y in min.1..=max.1 {
-        //     for x in min.0..=max.0 {
-        //         if field.elves.contains(&(x, y)) {
-        //             print!("#")
-        //         } else {
-        //             print!(".")
-        //         }
-        //     }
-        //     println!();
-        // }
-
+        let (min, max) = field.elves.bounds().unwrap();
         let diff = (max.0 - min.0, max.1 - min.1);
 
         Ok(((diff.0 + 1) * (diff.1 + 1)) as usize - field.elves.len())
@@ -70,36 +55,28 @@ impl Solver for Problem {
 }
 
 struct Field {
-    elves: HashSet<(isize, isize)>,
+    elves: SparseGrid<Elf>,
     step: usize,
 }
 
 impl Field {
-    fn new(elves: HashSet<(isize, isize)>) -> Self {
+    fn new(elves: SparseGrid<Elf>) -> Self {
         Self { elves, step: 0 }
     }
 
     fn turn(&mut self) -> bool {
         let checks = vec![
-            (vec![(1, -1), (0, -1), (-1, -1)], (0, -1)),
-            (vec![(1, 1), (0, 1), (-1, 1)], (0, 1)),
-            (vec![(-1, -1), (-1, 0), (-1, 1)], (-1, 0)),
-            (vec![(1, -1), (1, 0), (1, 1)], (1, 0)),
+            (vec![(-1, 1), (-1, 0), (-1, -1)], (-1, 0)),
+            (vec![(1, 1), (1, 0), (1, -1)], (1, 0)),
+            (vec![(-1, -1), (0, -1), (1, -1)], (0, -1)),
+            (vec![(-1, 1), (0, 1), (1, 1)], (0, 1)),
         ];
 
         let mut aborted_positions = HashSet::new();
         let mut intentions = HashSet::new();
         let mut moves = HashMap::new();
-        for elf in self.elves.iter() {
-            if !self.elves.contains(&(elf.0 + 1, elf.1 - 1))
-                && !self.elves.contains(&(elf.0 + 1, elf.1))
-                && !self.elves.contains(&(elf.0 + 1, elf.1 + 1))
-                && !self.elves.contains(&(elf.0, elf.1 - 1))
-                && !self.elves.contains(&(elf.0, elf.1 + 1))
-                && !self.elves.contains(&(elf.0 - 1, elf.1 - 1))
-                && !self.elves.contains(&(elf.0 - 1, elf.1))
-                && !self.elves.contains(&(elf.0 - 1, elf.1 + 1))
-            {
+        for elf in self.elves.points() {
+            if self.elves.neighbours(*elf, &SURROUNDING).next().is_none() {
                 continue;
             }
 
@@ -118,18 +95,18 @@ impl Field {
             }
         }
 
-        let mut new_positions = HashSet::new();
+        let mut new_positions = SparseGrid::default();
         let mut some_moved = false;
-        for elf in self.elves.iter() {
+        for elf in self.elves.points() {
             if let Some(dest) = moves.get(elf) {
                 if !aborted_positions.contains(dest) {
                     some_moved = true;
-                    new_positions.insert(*dest);
+                    new_positions.insert(*dest, Elf);
                 } else {
-                    new_positions.insert(*elf);
+                    new_positions.insert(*elf, Elf);
                 }
             } else {
-                new_positions.insert(*elf);
+                new_positions.insert(*elf, Elf);
             }
         }
 
@@ -138,7 +115,7 @@ impl Field {
         return some_moved;
     }
 
-    fn run_check(&self, elf: &(isize, isize), check: &Vec<(isize, isize)>) -> bool {
+    fn run_check(&self, elf: &Point, check: &Vec<(isize, isize)>) -> bool {
         check
             .iter()
             .all(|deltas| !self.elves.contains(&(elf.0 + deltas.0, elf.1 + deltas.1)))
@@ -1,5 +1,7 @@
 use itertools::Itertools;
 
+use super::grid::{Grid, Pos, ORTHOGONAL};
+use super::search::layered_bfs;
 use super::Solver;
 use std::collections::HashSet;
 use std::fs::File;
@@ -10,109 +12,89 @@ pub struct Problem;
 #[derive(Clone)]
 pub struct Blizzard {
     direction: Direction,
-    position: (usize, usize),
+    position: Pos,
 }
 
 impl Blizzard {
-    fn step(&mut self, width: usize, height: usize) {
+    /// Moves one step, wrapping around inside the walls.
+    fn step(&mut self, basin: &Grid<Tile>) {
+        let (rows, cols) = (basin.rows(), basin.cols());
         self.position = match (self.direction, self.position) {
-            (Direction::Right, (x, y)) if x == width - 2 => (1, y),
-            (Direction::Right, (x, y)) => (x + 1, y),
-            (Direction::Left, (x, y)) if x == 1 => (width - 2, y),
-            (Direction::Left, (x, y)) => (x - 1, y),
-            (Direction::Up, (x, y)) if y == 1 => (x, height - 2),
-            (Direction::Up, (x, y)) => (x, y - 1),
-            (Direction::Down, (x, y)) if y == height - 2 => (x, 1),
-            (Direction::Down, (x, y)) => (x, y + 1),
+            (Direction::Right, (r, c)) if c == cols - 2 => (r, 1),
+            (Direction::Right, (r, c)) => (r, c + 1),
+            (Direction::Left, (r, 1)) => (r, cols - 2),
+            (Direction::Left, (r, c)) => (r, c - 1),
+            (Direction::Up, (1, c)) => (rows - 2, c),
+            (Direction::Up, (r, c)) => (r - 1, c),
+            (Direction::Down, (r, c)) if r == rows - 2 => (1, c),
+            (Direction::Down, (r, c)) => (r + 1, c),
         };
     }
 }
 
+#[derive(Clone, PartialEq)]
+pub enum Tile {
+    Wall,
+    Open,
+}
+
 #[derive(Clone)]
 pub struct Field {
-    width: usize,
-    height: usize,
+    basin: Grid<Tile>,
     blizzards: Vec<Blizzard>,
 }
 
 impl Field {
-    fn step(&mut self) -> HashSet<(usize, usize)> {
+    fn step(&mut self) -> HashSet<Pos> {
         self.blizzards
             .iter_mut()
             .map(|blizzard| {
-                blizzard.step(self.width, self.height);
+                blizzard.step(&self.basin);
                 blizzard.position
             })
             .collect()
     }
 
-    fn run(&mut self, start: (usize, usize), end: (usize, usize)) -> usize {
-        let mut positions = HashSet::new();
-        positions.insert(start);
+    fn start(&self) -> Pos {
+        (0, 1)
+    }
 
-        let mut step = 0;
-        loop {
-            let mut new_positions = HashSet::new();
-            step += 1;
-            let occupied = self.step();
+    fn end(&self) -> Pos {
+        (self.basin.rows() - 1, self.basin.cols() - 2)
+    }
 
-            for position in positions {
-                let adj: HashSet<_> = adjacent(self.width, self.height, &position)
-                    .into_iter()
-                    .filter(|p| !occupied.contains(p))
-                    .collect();
-
-                if adj.contains(&end) {
-                    // println!("{step} final");
-                    // for y in 0..self.height {
-                    //     for x in 0..self.width {
-                    //         if occupied.contains(&(x, y)) {
-                    //             print!("@");
-                    //         } else if end == (x, y) {
-                    //             print!("E");
-                    //         } else if x == 0
-                    //             || (y == 0 && x != 1)
-                    //             || x == self.width - 1
-                    //             || (y == self.height - 1 && x != self.width - 2)
-                    //         {
-                    //             print!("#")
-                    //         } else {
-                    //             print!(".");
-                    //         }
-                    //     }
-                    //     println!();
-                    // }
-
-                    return step;
+    /// Where an expedition at `position` can be next: staying put or one step
+    /// in any direction that's not a wall.
+    fn adjacent(&self, position: Pos) -> impl Iterator<Item = Pos> + '_ {
+        std::iter::once(position).chain(
+            self.basin
+                .neighbours(position, &ORTHOGONAL)
+                .filter(|p| self.basin[*p] == Tile::Open),
+        )
+    }
+
+    /// Minutes to go from `start` to `end`, with the blizzards carrying on
+    /// from where they are.
+    fn run(&mut self, start: Pos, end: Pos) -> Result<usize, String> {
+        let mut occupied = HashSet::new();
+        let mut minute = 0;
+        let found = layered_bfs(
+            [start],
+            |depth, position| {
+                if depth != minute {
+                    occupied = self.step();
+                    minute = depth;
                 }
+                self.adjacent(*position)
+                    .filter(|p| !occupied.contains(p))
+                    .collect_vec()
+            },
+            |position| *position == end,
+        );
 
-                adj.into_iter().for_each(|p| {
-                    new_positions.insert(p);
-                });
-            }
-
-            // println!("{step}");
-            // for y in 0..self.height {
-            //     for x in 0..self.width {
-            //         if occupied.contains(&(x, y)) {
-            //             print!("@");
-            //         } else if new_positions.contains(&(x, y)) {
-            //             print!("E");
-            //         } else if x == 0
-            //             || (y == 0 && x != 1)
-            //             || x == self.width - 1
-            //             || (y == self.height - 1 && x != self.width - 2)
-            //         {
-            //             print!("#")
-            //         } else {
-            //             print!(".");
-            //         }
-            //     }
-            //     println!();
-            // }
-
-            positions = new_positions;
-            // println!("{step} {:?}", positions);
+        match found {
+            Some((_, minutes)) => Ok(minutes),
+            None => Err(format!("no way from {start:?} to {end:?}")),
         }
     }
 }
@@ -123,80 +105,49 @@ impl Solver for Problem {
     type Output2 = usize;
 
     fn read_input(&self, file_reader: BufReader<&File>) -> Self::Input {
-        let lines = file_reader
-            .lines()
-            .map(|x| x.unwrap())
-            .map(|line| line.char_indices().collect_vec())
-            .collect_vec();
-
-        let height = lines.len();
-        let width = lines[0].len();
-        let blizzards = lines
-            .into_iter()
-            .enumerate()
-            .skip(1)
-            .flat_map(|(y, line)| {
-                line.into_iter()
-                    .filter(|(_, c)| *c != '#' && *c != '.')
-                    .map(|(x, c)| Blizzard {
-                        direction: match c {
-                            '>' => Direction::Right,
-                            'v' => Direction::Down,
-                            '<' => Direction::Left,
-                            '^' => Direction::Up,
-                            _ => panic!("unknown char"),
-                        },
-                        position: (x, y),
-                    })
-                    .collect_vec()
-            })
-            .collect_vec();
-        Field {
-            width,
-            height,
-            blizzards,
-        }
+        let text = file_reader.lines().map(|x| x.unwrap()).join("\n");
+
+        let mut blizzards = vec![];
+        let basin = Grid::parse(&text, |position, c| {
+            let direction = match c {
+                '#' => return Ok(Tile::Wall),
+                '.' => return Ok(Tile::Open),
+                '>' => Direction::Right,
+                'v' => Direction::Down,
+                '<' => Direction::Left,
+                '^' => Direction::Up,
+                _ => return Err(format!("unknown char {c}")),
+            };
+            blizzards.push(Blizzard {
+                direction,
+                position,
+            });
+            Ok(Tile::Open)
+        })
+        .unwrap();
+
+        Field { basin, blizzards }
     }
 
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String> {
         let mut field = input.clone();
 
-        Ok(field.run((1, 0), (field.width - 2, field.height - 1)))
+        field.run(field.start(), field.end())
     }
 
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String> {
         let mut field = input.clone();
 
-        let start = (1, 0);
-        let end = (field.width - 2, field.height - 1);
-        let mut res = field.run(start, end);
-        res += field.run(end, start);
-        res += field.run(start, end);
+        let start = field.start();
+        let end = field.end();
+        let mut res = field.run(start, end)?;
+        res += field.run(end, start)?;
+        res += field.run(start, end)?;
 
         Ok(res)
     }
 }
 
-fn adjacent(width: usize, height: usize, position: &(usize, usize)) -> Vec<(usize, usize)> {
-    let (x, y) = position.clone();
-    let mut res = vec![(x, y)];
-
-    if y > 1 || (y == 1 && x == 1) {
-        res.push((x, y - 1));
-    }
-    if y < height - 2 || (y == height - 2 && x == width - 2) {
-        res.push((x, y + 1));
-    }
-    if x > 1 && y != height - 1 {
-        res.push((x - 1, y));
-    }
-    if x < width - 2 && y != 0 {
-        res.push((x + 1, y));
-    }
-
-    res
-}
-
 #[derive(Clone, Copy)]
 enum Direction {
     Right = 0,
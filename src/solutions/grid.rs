@@ -0,0 +1,360 @@
+use itertools::Itertools;
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+/// Position in a grid as (row, col), rows going down.
+pub type Pos = (usize, usize);
+
+/// Position in a sparse grid, unbounded in every direction. Also (row, col).
+pub type Point = (isize, isize);
+
+/// Steps to the four orthogonal neighbours, as (row, col) deltas.
+pub const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Steps to all eight surrounding cells.
+pub const SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Dense rectangular grid, stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(rows: usize, cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            rows,
+            cols,
+            cells: vec![value; rows * cols],
+        }
+    }
+
+    pub fn from_fn(rows: usize, cols: usize, f: impl FnMut(Pos) -> T) -> Self {
+        Grid {
+            rows,
+            cols,
+            cells: (0..rows).cartesian_product(0..cols).map(f).collect(),
+        }
+    }
+
+    /// Builds a grid from a character map, one line per row. All lines must
+    /// be the same length.
+    pub fn parse(s: &str, f: impl FnMut(Pos, char) -> Result<T, String>) -> Result<Self, String>
+    where
+        T: Clone,
+    {
+        Self::parse_lines(s, None, f)
+    }
+
+    /// Like `parse`, but short lines are filled up with `pad`.
+    pub fn parse_padded(
+        s: &str,
+        pad: T,
+        f: impl FnMut(Pos, char) -> Result<T, String>,
+    ) -> Result<Self, String>
+    where
+        T: Clone,
+    {
+        Self::parse_lines(s, Some(pad), f)
+    }
+
+    fn parse_lines(
+        s: &str,
+        pad: Option<T>,
+        mut f: impl FnMut(Pos, char) -> Result<T, String>,
+    ) -> Result<Self, String>
+    where
+        T: Clone,
+    {
+        let lines = s.lines().collect_vec();
+        let cols = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+        let mut cells = Vec::with_capacity(lines.len() * cols);
+        for (row, line) in lines.iter().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                cells.push(f((row, col), c)?);
+            }
+
+            let missing = cols - line.chars().count();
+            match &pad {
+                _ if missing == 0 => {}
+                Some(pad) => cells.extend(std::iter::repeat_n(pad.clone(), missing)),
+                None => return Err(format!("line {} is {missing} short", row + 1)),
+            }
+        }
+
+        Ok(Grid {
+            rows: lines.len(),
+            cols,
+            cells,
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if pos.0 < self.rows && pos.1 < self.cols {
+            Some(&self.cells[pos.0 * self.cols + pos.1])
+        } else {
+            None
+        }
+    }
+
+    /// Moves `pos` by `delta`, or `None` if that leaves the grid.
+    pub fn offset(&self, pos: Pos, delta: (isize, isize)) -> Option<Pos> {
+        let row = pos.0.checked_add_signed(delta.0)?;
+        let col = pos.1.checked_add_signed(delta.1)?;
+        self.get((row, col)).map(|_| (row, col))
+    }
+
+    /// Moves `pos` by `delta`, coming back on the other side of the grid
+    /// when leaving it.
+    pub fn wrapping_offset(&self, pos: Pos, delta: (isize, isize)) -> Pos {
+        (
+            (pos.0 as isize + delta.0).rem_euclid(self.rows as isize) as usize,
+            (pos.1 as isize + delta.1).rem_euclid(self.cols as isize) as usize,
+        )
+    }
+
+    /// Neighbours of `pos` inside the grid, for the given steps (usually
+    /// `ORTHOGONAL` or `SURROUNDING`).
+    pub fn neighbours<'a>(
+        &'a self,
+        pos: Pos,
+        deltas: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Pos> + 'a {
+        deltas.iter().filter_map(move |d| self.offset(pos, *d))
+    }
+
+    /// Cells from `pos` (excluded) to the edge of the grid, going in `delta`.
+    pub fn ray(&self, pos: Pos, delta: (isize, isize)) -> impl Iterator<Item = Pos> + '_ {
+        std::iter::successors(self.offset(pos, delta), move |p| self.offset(*p, delta))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        let cols = self.cols;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, v)| ((i / cols, i % cols), v))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn position(&self, mut f: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, v)| f(v)).map(|(pos, _)| pos)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            rows: self.rows,
+            cols: self.cols,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &Self::Output {
+        assert!(pos.1 < self.cols, "column {} out of {}", pos.1, self.cols);
+        &self.cells[pos.0 * self.cols + pos.1]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        assert!(pos.1 < self.cols, "column {} out of {}", pos.1, self.cols);
+        &mut self.cells[pos.0 * self.cols + pos.1]
+    }
+}
+
+/// One line per row, without a trailing newline.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.rows {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            for cell in self.row(row) {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Grid that only stores the occupied cells, for things that spread out
+/// without bounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    /// Builds a grid from a character map, keeping the cells `f` returns
+    /// something for.
+    pub fn parse(s: &str, mut f: impl FnMut(char) -> Option<T>) -> Self {
+        let cells = s
+            .lines()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter_map(|(col, c)| Some(((row as isize, col as isize), f(c)?)))
+                    .collect_vec()
+            })
+            .collect();
+
+        SparseGrid { cells }
+    }
+
+    pub fn insert(&mut self, point: Point, value: T) -> Option<T> {
+        self.cells.insert(point, value)
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        self.cells.contains_key(point)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &Point> {
+        self.cells.keys()
+    }
+
+    /// Occupied cells around `point`, for the given steps.
+    pub fn neighbours<'a>(
+        &'a self,
+        point: Point,
+        deltas: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Point> + 'a {
+        deltas
+            .iter()
+            .map(move |d| (point.0 + d.0, point.1 + d.1))
+            .filter(|p| self.contains(p))
+    }
+
+    /// Smallest and largest corners of the occupied area.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        if self.is_empty() {
+            return None;
+        }
+        let (min_row, max_row) = self.points().map(|p| p.0).minmax().into_option()?;
+        let (min_col, max_col) = self.points().map(|p| p.1).minmax().into_option()?;
+        Some(((min_row, min_col), (max_row, max_col)))
+    }
+}
+
+/// Draws the occupied area, with `.` for the free cells.
+impl<T: Display> Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some((min, max)) = self.bounds() else {
+            return Ok(());
+        };
+        for row in min.0..=max.0 {
+            if row > min.0 {
+                writeln!(f)?;
+            }
+            for col in min.1..=max.1 {
+                match self.cells.get(&(row, col)) {
+                    Some(cell) => write!(f, "{cell}")?,
+                    None => write!(f, ".")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(s: &str) -> Result<Grid<u32>, String> {
+        Grid::parse(s, |_, c| c.to_digit(10).ok_or(format!("not a digit {c}")))
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid[(1, 0)], 4);
+        assert_eq!(grid.row(0), [1, 2, 3]);
+        assert_eq!(grid.to_string(), "123\n456");
+    }
+
+    #[test]
+    fn parse_rejects_short_lines_unless_padded() {
+        assert_eq!(digits("123\n45"), Err("line 2 is 1 short".to_owned()));
+        assert!(digits("12x").is_err());
+
+        let padded = Grid::parse_padded("#\n.#", false, |_, c| Ok(c == '#')).unwrap();
+        assert_eq!(padded.row(0), [true, false]);
+    }
+
+    #[test]
+    fn offsets_stay_inside() {
+        let grid = Grid::new(3, 4, 0);
+        assert_eq!(grid.offset((0, 0), (-1, 0)), None);
+        assert_eq!(grid.offset((2, 3), (0, 1)), None);
+        assert_eq!(grid.offset((1, 1), (1, 2)), Some((2, 3)));
+        assert_eq!(grid.wrapping_offset((0, 0), (-1, -1)), (2, 3));
+        assert_eq!(grid.neighbours((0, 0), &ORTHOGONAL).count(), 2);
+        assert_eq!(grid.neighbours((1, 1), &SURROUNDING).count(), 8);
+        assert_eq!(grid.ray((1, 1), (0, 1)).collect_vec(), [(1, 2), (1, 3)]);
+    }
+
+    #[test]
+    fn sparse_grid_bounds_and_display() {
+        let mut grid = SparseGrid::parse(".#\n#.", |c| (c == '#').then_some('#'));
+        assert_eq!(grid.len(), 2);
+        grid.insert((-1, 2), '#');
+        assert_eq!(grid.bounds(), Some(((-1, 0), (1, 2))));
+        assert_eq!(grid.to_string(), "..#\n.#.\n#..");
+        assert_eq!(grid.neighbours((0, 0), &SURROUNDING).count(), 2);
+    }
+}
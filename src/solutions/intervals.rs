@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// Set of integers stored as disjoint inclusive ranges, kept sorted and
+/// merged (touching ranges like 1..=3 and 4..=5 become 1..=5).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IntervalSet {
+    /// start -> end, both included
+    ranges: BTreeMap<isize, isize>,
+}
+
+impl IntervalSet {
+    pub fn range(start: isize, end: isize) -> Self {
+        let mut set = IntervalSet::default();
+        set.insert(start, end);
+        set
+    }
+
+    /// Adds `start..=end`. Does nothing if `end < start`.
+    pub fn insert(&mut self, start: isize, end: isize) {
+        if end < start {
+            return;
+        }
+
+        // Ranges that overlap or touch the new one, going backwards from
+        // the last one starting before it ends
+        let merged = self
+            .ranges
+            .range(..=end.saturating_add(1))
+            .rev()
+            .take_while(|(_, e)| **e >= start.saturating_sub(1))
+            .map(|(s, e)| (*s, *e))
+            .collect::<Vec<_>>();
+
+        let (mut start, mut end) = (start, end);
+        for (s, e) in merged {
+            self.ranges.remove(&s);
+            start = start.min(s);
+            end = end.max(e);
+        }
+        self.ranges.insert(start, end);
+    }
+
+    /// Takes `start..=end` out, splitting the ranges it goes through.
+    fn remove(&mut self, start: isize, end: isize) {
+        if end < start {
+            return;
+        }
+
+        let cut = self
+            .ranges
+            .range(..=end)
+            .rev()
+            .take_while(|(_, e)| **e >= start)
+            .map(|(s, e)| (*s, *e))
+            .collect::<Vec<_>>();
+
+        for (s, e) in cut {
+            self.ranges.remove(&s);
+            if s < start {
+                self.ranges.insert(s, start - 1);
+            }
+            if e > end {
+                self.ranges.insert(end + 1, e);
+            }
+        }
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut res = self.clone();
+        for (start, end) in other.ranges() {
+            res.insert(start, end);
+        }
+        res
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut res = IntervalSet::default();
+        let mut left = self.ranges().peekable();
+        let mut right = other.ranges().peekable();
+
+        while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+            res.insert(l.0.max(r.0), l.1.min(r.1));
+            // Whichever ends first can't overlap anything else
+            if l.1 < r.1 {
+                left.next();
+            } else {
+                right.next();
+            }
+        }
+        res
+    }
+
+    pub fn subtract(&self, other: &IntervalSet) -> IntervalSet {
+        let mut res = self.clone();
+        for (start, end) in other.ranges() {
+            res.remove(start, end);
+        }
+        res
+    }
+
+    pub fn contains(&self, value: isize) -> bool {
+        self.ranges
+            .range(..=value)
+            .next_back()
+            .is_some_and(|(_, end)| *end >= value)
+    }
+
+    /// Whether every value in `other` is in this set too.
+    pub fn covers(&self, other: &IntervalSet) -> bool {
+        self.union(other) == *self
+    }
+
+    /// How many values are in the set.
+    pub fn len(&self) -> usize {
+        self.ranges().map(|(s, e)| (e - s + 1) as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Ranges in order, as (start, end) with both included.
+    pub fn ranges(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.ranges.iter().map(|(s, e)| (*s, *e))
+    }
+
+    /// Ranges of `start..=end` that are not in the set.
+    pub fn gaps(&self, start: isize, end: isize) -> impl Iterator<Item = (isize, isize)> {
+        IntervalSet::range(start, end)
+            .subtract(self)
+            .ranges
+            .into_iter()
+    }
+}
+
+impl FromIterator<(isize, isize)> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = (isize, isize)>>(iter: T) -> Self {
+        let mut set = IntervalSet::default();
+        for (start, end) in iter {
+            set.insert(start, end);
+        }
+        set
+    }
+}
+
+/// Ranges separated by commas, like `-3..=-1,5,7..=9`.
+impl Display for IntervalSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (start, end)) in self.ranges().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            if start == end {
+                write!(f, "{start}")?;
+            } else {
+                write!(f, "{start}..={end}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[(isize, isize)]) -> IntervalSet {
+        ranges.iter().copied().collect()
+    }
+
+    #[test]
+    fn insert_merges_touching_and_overlapping_ranges() {
+        let mut s = set(&[(1, 3), (10, 12)]);
+        s.insert(4, 5);
+        assert_eq!(s.ranges().collect::<Vec<_>>(), [(1, 5), (10, 12)]);
+        s.insert(5, 11);
+        assert_eq!(s.ranges().collect::<Vec<_>>(), [(1, 12)]);
+        s.insert(-4, -2);
+        assert_eq!(s.ranges().collect::<Vec<_>>(), [(-4, -2), (1, 12)]);
+        s.insert(3, 2);
+        assert_eq!(s.len(), 15);
+    }
+
+    #[test]
+    fn intersection() {
+        let a = set(&[(0, 5), (10, 20)]);
+        let b = set(&[(3, 12), (15, 15), (19, 30)]);
+        assert_eq!(
+            a.intersection(&b),
+            set(&[(3, 5), (10, 12), (15, 15), (19, 20)])
+        );
+        assert!(a.intersection(&set(&[(6, 9)])).is_empty());
+    }
+
+    #[test]
+    fn subtract() {
+        let a = set(&[(0, 10), (20, 30)]);
+        assert_eq!(
+            a.subtract(&set(&[(-5, 0), (4, 6), (10, 22)])),
+            set(&[(1, 3), (7, 9), (23, 30)])
+        );
+        assert!(a.subtract(&set(&[(-10, 40)])).is_empty());
+    }
+
+    #[test]
+    fn gaps() {
+        let s = set(&[(-2, 2), (5, 6)]);
+        assert_eq!(
+            s.gaps(-5, 10).collect::<Vec<_>>(),
+            [(-5, -3), (3, 4), (7, 10)]
+        );
+        assert_eq!(s.gaps(0, 2).count(), 0);
+    }
+
+    #[test]
+    fn contains() {
+        let s = set(&[(-2, 2), (5, 5)]);
+        assert!(s.contains(-2) && s.contains(2) && s.contains(5));
+        assert!(!s.contains(-3) && !s.contains(3) && !s.contains(6));
+    }
+
+    #[test]
+    fn covers() {
+        let s = set(&[(0, 10), (20, 30)]);
+        assert!(s.covers(&set(&[(0, 3), (25, 30)])));
+        assert!(s.covers(&IntervalSet::default()));
+        assert!(!s.covers(&set(&[(9, 11)])));
+    }
+
+    #[test]
+    fn display_keeps_negative_bounds_apart() {
+        assert_eq!(set(&[(-2, 24)]).to_string(), "-2..=24");
+        assert_eq!(
+            set(&[(-5, -3), (0, 0), (2, 4)]).to_string(),
+            "-5..=-3,0,2..=4"
+        );
+    }
+}
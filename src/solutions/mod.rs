@@ -1,3 +1,4 @@
+mod cycle;
 mod day01;
 mod day02;
 mod day03;
@@ -23,38 +24,49 @@ mod day22;
 mod day23;
 mod day24;
 mod day25;
+mod grid;
+mod intervals;
+mod search;
 mod solver;
 
-pub use solver::Solver;
+pub use solver::{Mode, Solver};
 
 pub fn solve(day: usize, parts: usize) {
+    run(day, Mode::Solve(parts))
+}
+
+pub fn inspect(day: usize, args: Vec<String>) {
+    run(day, Mode::Inspect(args))
+}
+
+fn run(day: usize, mode: Mode) {
     let filename = format!("inputs/{:02}", day);
     match day {
-        1 => day01::Problem.solve(filename, parts),
-        2 => day02::Problem.solve(filename, parts),
-        3 => day03::Problem.solve(filename, parts),
-        4 => day04::Problem.solve(filename, parts),
-        5 => day05::Problem.solve(filename, parts),
-        6 => day06::Problem.solve(filename, parts),
-        7 => day07::Problem.solve(filename, parts),
-        8 => day08::Problem.solve(filename, parts),
-        9 => day09::Problem.solve(filename, parts),
-        10 => day10::Problem.solve(filename, parts),
-        11 => day11::Problem.solve(filename, parts),
-        12 => day12::Problem.solve(filename, parts),
-        13 => day13::Problem.solve(filename, parts),
-        14 => day14::Problem.solve(filename, parts),
-        15 => day15::Problem.solve(filename, parts),
-        16 => day16::Problem.solve(filename, parts),
-        17 => day17::Problem.solve(filename, parts),
-        18 => day18::Problem.solve(filename, parts),
-        19 => day19::Problem.solve(filename, parts),
-        20 => day20::Problem.solve(filename, parts),
-        21 => day21::Problem.solve(filename, parts),
-        22 => day22::Problem.solve(filename, parts),
-        23 => day23::Problem.solve(filename, parts),
-        24 => day24::Problem.solve(filename, parts),
-        25 => day25::Problem.solve(filename, parts),
+        1 => day01::Problem.run(filename, mode),
+        2 => day02::Problem.run(filename, mode),
+        3 => day03::Problem.run(filename, mode),
+        4 => day04::Problem.run(filename, mode),
+        5 => day05::Problem.run(filename, mode),
+        6 => day06::Problem.run(filename, mode),
+        7 => day07::Problem.run(filename, mode),
+        8 => day08::Problem.run(filename, mode),
+        9 => day09::Problem.run(filename, mode),
+        10 => day10::Problem.run(filename, mode),
+        11 => day11::Problem.run(filename, mode),
+        12 => day12::Problem.run(filename, mode),
+        13 => day13::Problem.run(filename, mode),
+        14 => day14::Problem.run(filename, mode),
+        15 => day15::Problem.run(filename, mode),
+        16 => day16::Problem.run(filename, mode),
+        17 => day17::Problem.run(filename, mode),
+        18 => day18::Problem.run(filename, mode),
+        19 => day19::Problem.run(filename, mode),
+        20 => day20::Problem.run(filename, mode),
+        21 => day21::Problem.run(filename, mode),
+        22 => day22::Problem.run(filename, mode),
+        23 => day23::Problem.run(filename, mode),
+        24 => day24::Problem.run(filename, mode),
+        25 => day25::Problem.run(filename, mode),
         _ => panic!("day not implemented"),
     }
 }
@@ -0,0 +1,388 @@
+use itertools::Itertools;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt::Display;
+use std::hash::Hash;
+
+/// How much work a search went through.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Stats {
+    /// States whose successors were generated.
+    pub expanded: usize,
+    /// States thrown away: already seen, bounded out or left out of a beam.
+    pub discarded: usize,
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} states expanded, {} discarded",
+            self.expanded, self.discarded
+        )
+    }
+}
+
+/// What a graph search found: the cost of every state it reached and where
+/// it came from, so paths are only rebuilt when asked for.
+pub struct Explored<S> {
+    reached: HashMap<S, (usize, Option<S>)>,
+    pub goal: Option<S>,
+    pub stats: Stats,
+}
+
+impl<S: Clone + Eq + Hash> Explored<S> {
+    fn new() -> Self {
+        Explored {
+            reached: HashMap::new(),
+            goal: None,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Best known cost to `state`. Final for everything when the search ran
+    /// out of states, and for the goal otherwise.
+    pub fn cost(&self, state: &S) -> Option<usize> {
+        self.reached.get(state).map(|(cost, _)| *cost)
+    }
+
+    /// States from a start to `state`, both included.
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        let mut path = vec![state.clone()];
+        while let Some(parent) = &self.reached.get(path.last().unwrap())?.1 {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    fn reach(&mut self, state: S, cost: usize, parent: Option<S>) {
+        self.reached.insert(state, (cost, parent));
+    }
+}
+
+/// Breadth-first search from all of `starts` at once, stopping at the
+/// first state `goal` accepts.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Explored<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut explored = Explored::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if explored.cost(&start).is_none() {
+            explored.reach(start.clone(), 0, None);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(state) = queue.pop_front() {
+        if goal(&state) {
+            explored.goal = Some(state);
+            break;
+        }
+
+        explored.stats.expanded += 1;
+        let cost = explored.cost(&state).unwrap() + 1;
+        for next in successors(&state) {
+            if explored.cost(&next).is_some() {
+                explored.stats.discarded += 1;
+                continue;
+            }
+            explored.reach(next.clone(), cost, Some(state.clone()));
+            queue.push_back(next);
+        }
+    }
+
+    explored
+}
+
+/// Every state reachable from `starts`.
+pub fn flood_fill<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+) -> HashSet<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    bfs(starts, successors, |_| false)
+        .reached
+        .into_keys()
+        .collect()
+}
+
+/// Cheapest path search with step costs.
+pub fn dijkstra<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Explored<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    astar(starts, successors, |_| 0, goal)
+}
+
+/// Cheapest path search guided by `heuristic`, which must never say a state
+/// is further from the goal than it is.
+pub fn astar<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> usize,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Explored<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    let mut explored = Explored::new();
+    // The heap holds (estimate, cost, index in `queued`), so states don't
+    // need to be ordered.
+    let mut queue = BinaryHeap::new();
+    let mut queued = vec![];
+    for start in starts {
+        if explored.cost(&start).is_none() {
+            explored.reach(start.clone(), 0, None);
+            queue.push(Reverse((heuristic(&start), 0, queued.len())));
+            queued.push(start);
+        }
+    }
+
+    while let Some(Reverse((_, cost, index))) = queue.pop() {
+        let state = queued[index].clone();
+        if explored.cost(&state).unwrap() < cost {
+            explored.stats.discarded += 1;
+            continue;
+        }
+        if goal(&state) {
+            explored.goal = Some(state);
+            break;
+        }
+
+        explored.stats.expanded += 1;
+        for (next, step) in successors(&state) {
+            let next_cost = cost + step;
+            if explored.cost(&next).is_some_and(|known| known <= next_cost) {
+                explored.stats.discarded += 1;
+                continue;
+            }
+            explored.reach(next.clone(), next_cost, Some(state.clone()));
+            queue.push(Reverse((
+                next_cost + heuristic(&next),
+                next_cost,
+                queued.len(),
+            )));
+            queued.push(next);
+        }
+    }
+
+    explored
+}
+
+/// Breadth-first search that only keeps the current layer, for graphs that
+/// change with every step (so `successors` also gets the depth it's
+/// building). Returns the first goal found and its depth.
+pub fn layered_bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(usize, &S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Option<(S, usize)>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut layer: HashSet<S> = starts.into_iter().collect();
+    let mut depth = 0;
+    while !layer.is_empty() {
+        if let Some(found) = layer.iter().find(|s| goal(s)) {
+            return Some((found.clone(), depth));
+        }
+
+        depth += 1;
+        let mut next = HashSet::new();
+        for state in layer.iter() {
+            next.extend(successors(depth, state));
+        }
+        layer = next;
+    }
+
+    None
+}
+
+/// State of an optimisation search. Every state is an answer worth `value`,
+/// and nothing reachable from it is worth more than `bound`.
+pub trait Bounded: Sized {
+    fn successors(&self) -> Vec<Self>;
+    fn value(&self) -> usize;
+    fn bound(&self) -> usize;
+}
+
+/// Depth-first search for the best value, skipping states that can't beat
+/// the best one found so far.
+pub fn branch_and_bound<S: Bounded>(start: S) -> (usize, Stats) {
+    let mut stats = Stats::default();
+    let mut best = start.value();
+    let mut stack = vec![start];
+
+    while let Some(state) = stack.pop() {
+        if state.bound() <= best {
+            stats.discarded += 1;
+            continue;
+        }
+
+        stats.expanded += 1;
+        let next = state.successors();
+        best = next.iter().map(|s| s.value()).fold(best, usize::max);
+        // Most promising last, so it's looked at first.
+        stack.extend(next.into_iter().sorted_by_key(|s| s.bound()));
+    }
+
+    (best, stats)
+}
+
+/// Goes one layer at a time keeping only the `width` states with the
+/// highest bound. Fast, but can miss the best value.
+pub fn beam<S: Bounded>(start: S, width: usize) -> (usize, Stats) {
+    let mut stats = Stats::default();
+    let mut best = start.value();
+    let mut layer = vec![start];
+
+    while !layer.is_empty() {
+        stats.expanded += layer.len();
+        let next = layer.iter().flat_map(|s| s.successors()).collect_vec();
+        best = next.iter().map(|s| s.value()).fold(best, usize::max);
+
+        stats.discarded += next.len().saturating_sub(width);
+        layer = next
+            .into_iter()
+            .sorted_by_key(|s| Reverse(s.bound()))
+            .take(width)
+            .collect();
+    }
+
+    (best, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Small weighted graph where the direct edge 0 -> 3 costs more than
+    /// going through 1 and 2.
+    fn edges(node: &usize) -> Vec<(usize, usize)> {
+        match node {
+            0 => vec![(1, 1), (3, 10)],
+            1 => vec![(2, 1)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn bfs_finds_the_fewest_steps() {
+        let found = bfs([0], |n| edges(n).into_iter().map(|(n, _)| n), |n| *n == 3);
+        assert_eq!(found.goal, Some(3));
+        assert_eq!(found.cost(&3), Some(1));
+        assert_eq!(found.path_to(&3), Some(vec![0, 3]));
+    }
+
+    #[test]
+    fn dijkstra_and_astar_find_the_cheapest_path() {
+        let found = dijkstra([0], edges, |n| *n == 3);
+        assert_eq!(found.cost(&3), Some(3));
+        assert_eq!(found.path_to(&3), Some(vec![0, 1, 2, 3]));
+
+        let found = astar([0], edges, |n| 3 - n, |n| *n == 3);
+        assert_eq!(found.cost(&3), Some(3));
+        assert_eq!(found.path_to(&3), Some(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn searches_without_a_goal_reach_everything() {
+        let everywhere = dijkstra([2, 0], edges, |_| false);
+        assert_eq!(everywhere.goal, None);
+        assert_eq!(everywhere.cost(&3), Some(1));
+        assert_eq!(everywhere.path_to(&3), Some(vec![2, 3]));
+
+        let reached = flood_fill([1], |n| edges(n).into_iter().map(|(n, _)| n));
+        assert_eq!(reached, HashSet::from([1, 2, 3]));
+    }
+
+    #[test]
+    fn layered_bfs_passes_the_depth() {
+        // Only even numbers are open on odd depths, which takes one step more
+        // than the 3 it would otherwise
+        let found = layered_bfs(
+            [0],
+            |depth, n: &usize| {
+                [n + 1, n + 2]
+                    .into_iter()
+                    .filter(move |n| depth % 2 == 0 || n % 2 == 0)
+            },
+            |n| *n == 5,
+        );
+        assert_eq!(found, Some((5, 4)));
+    }
+
+    /// Picks items in order, each one taken or not, without going over 10.
+    #[derive(Clone)]
+    struct Knapsack {
+        next: usize,
+        weight: usize,
+        value: usize,
+    }
+
+    const ITEMS: [(usize, usize); 4] = [(5, 10), (4, 40), (6, 30), (3, 50)];
+
+    impl Bounded for Knapsack {
+        fn successors(&self) -> Vec<Self> {
+            let Some((weight, value)) = ITEMS.get(self.next) else {
+                return vec![];
+            };
+            let skip = Knapsack {
+                next: self.next + 1,
+                ..self.clone()
+            };
+            let mut next = vec![skip];
+            if self.weight + weight <= 10 {
+                next.push(Knapsack {
+                    next: self.next + 1,
+                    weight: self.weight + weight,
+                    value: self.value + value,
+                });
+            }
+            next
+        }
+
+        fn value(&self) -> usize {
+            self.value
+        }
+
+        fn bound(&self) -> usize {
+            self.value + ITEMS[self.next..].iter().map(|(_, v)| v).sum::<usize>()
+        }
+    }
+
+    #[test]
+    fn branch_and_bound_finds_the_best_value() {
+        let start = Knapsack {
+            next: 0,
+            weight: 0,
+            value: 0,
+        };
+        let (best, stats) = branch_and_bound(start.clone());
+        assert_eq!(best, 90);
+        assert!(stats.discarded > 0);
+
+        assert_eq!(beam(start.clone(), 100).0, 90);
+        assert!(beam(start, 1).0 <= 90);
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt::Display;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Seek, SeekFrom};
+use std::slice::Iter;
+use std::str::FromStr;
 use std::time::Instant;
 
 macro_rules! printResult {
@@ -17,6 +19,13 @@ macro_rules! printResult {
     };
 }
 
+/// What to do with a day's input: print the answers for the selected parts,
+/// or hand the extra arguments to the day's own tooling.
+pub enum Mode {
+    Solve(usize),
+    Inspect(Vec<String>),
+}
+
 pub trait Solver {
     type Input;
     type Output1: Display;
@@ -26,6 +35,24 @@ pub trait Solver {
     fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, String>;
     fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, String>;
 
+    /// Extra tooling beyond the puzzle answers, driven by `inspect` arguments.
+    fn inspect(&self, _input: &Self::Input, _args: &[String]) -> Result<(), String> {
+        Err("nothing to inspect for this day".to_owned())
+    }
+
+    fn run(&self, filename: String, mode: Mode) {
+        match mode {
+            Mode::Solve(parts) => self.solve(filename, parts),
+            Mode::Inspect(args) => {
+                let file = File::open(filename).expect("input file not found");
+                let input = self.read_input(BufReader::new(&file));
+                if let Err(val) = self.inspect(&input, &args) {
+                    println!("Inspect errored: {val}");
+                }
+            }
+        }
+    }
+
     fn solve(&self, filename: String, parts: usize) {
         let file = File::open(filename).expect("input file not found");
         let input = self.read_input(BufReader::new(&file));
@@ -52,3 +79,66 @@ fn get_elapsed(start: Instant) -> String {
         _ => format!("{} s", elapsed.as_secs()),
     }
 }
+
+/// Own handle on the input file, for days that stream it instead of
+/// loading it in `read_input`.
+pub fn keep_file(file_reader: BufReader<&File>) -> File {
+    file_reader.get_ref().try_clone().unwrap()
+}
+
+/// Reader over a file kept by `keep_file`, from the start.
+pub fn rewind(input: &File) -> BufReader<&File> {
+    let mut reader = BufReader::new(input);
+    reader.seek(SeekFrom::Start(0)).unwrap();
+    reader
+}
+
+/// Parses a number given to `inspect`.
+pub fn number<T: FromStr>(v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("wrong number {v}"))
+}
+
+/// The `inspect` arguments left after the flag being handled, so it can
+/// take its value.
+pub struct Flag<'a> {
+    name: &'a str,
+    rest: Iter<'a, String>,
+}
+
+impl<'a> Flag<'a> {
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub fn value(&mut self) -> Result<&'a String, String> {
+        let name = self.name;
+        self.rest
+            .next()
+            .ok_or_else(|| format!("missing value for {name}"))
+    }
+
+    pub fn number<T: FromStr>(&mut self) -> Result<T, String> {
+        number(self.value()?)
+    }
+}
+
+/// Splits `args` into flags and the remaining positional arguments. Each
+/// argument goes through `flag` first, which says whether it took it.
+pub fn split_flags<'a>(
+    args: &'a [String],
+    mut flag: impl FnMut(&mut Flag<'a>) -> Result<bool, String>,
+) -> Result<Vec<&'a String>, String> {
+    let mut positional = vec![];
+    let mut rest = args.iter();
+
+    while let Some(arg) = rest.next() {
+        let mut current = Flag { name: arg, rest };
+        let taken = flag(&mut current)?;
+        rest = current.rest;
+        if !taken {
+            positional.push(arg);
+        }
+    }
+
+    Ok(positional)
+}